@@ -0,0 +1,578 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// Arquivo de configuração procurado no diretório atual
+const DEFAULT_CONFIG_FILE: &str = "cortex.toml";
+
+// Prefixo das variáveis de ambiente que sobrescrevem a configuração
+//
+// Os níveis são separados por `__`, por exemplo
+// `CORTEX__MODULES__SHODAN__SEARCH_TIMEOUT_SECS=20`.
+const ENV_PREFIX: &str = "CORTEX__";
+
+// Variáveis de ambiente históricas e a chave de configuração equivalente
+//
+// Valem como qualquer sobrescrita de ambiente (vencem o arquivo), mas a
+// variável `CORTEX__*` da mesma chave, se definida, tem prioridade.
+const ENV_ALIASES: &[(&str, &str)] = &[
+    ("SHODAN_API_KEY", "modules.shodan.api_key"),
+    ("CORTEX_DB_PATH", "storage.path"),
+    ("CORTEX_WEBHOOK_URL", "watch.webhook"),
+    ("CORTEX_MODULE_TIMEOUT", "scan.module_timeout_secs"),
+];
+
+// Lista, separada por vírgulas, de módulos a desabilitar (ex.: "shodan,wayback machine")
+const DISABLED_MODULES_VAR: &str = "CORTEX_DISABLED_MODULES";
+
+// Nomes aceitos em `CORTEX_DISABLED_MODULES`: chave da configuração e nome do módulo
+const MODULE_NAMES: &[(&str, &str)] = &[
+    ("crtsh", "crt.sh"),
+    ("certspotter", "certspotter"),
+    ("shodan", "shodan"),
+    ("wayback", "wayback machine"),
+    ("dorker", "dork generator"),
+];
+
+// Configuração completa da aplicação
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub scan: ScanConfig,
+    pub storage: StorageConfig,
+    pub watch: WatchConfig,
+    pub http: HttpConfig,
+    pub dns: DnsConfig,
+    pub takeover: TakeoverConfig,
+    pub ipinfo: IpInfoConfig,
+    pub modules: ModulesConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub bind: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { bind: "127.0.0.1:3300".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    // Tempo máximo de execução de cada módulo
+    pub module_timeout_secs: u64,
+    // Maior bloco CIDR aceito como alvo, em endereços
+    pub max_network_hosts: usize,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self { module_timeout_secs: 120, max_network_hosts: 256 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub path: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self { path: "cortex-passivemap.db".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    // Webhook padrão dos alertas de monitoramento
+    pub webhook: Option<String>,
+}
+
+// Modo de operação do cliente HTTP compartilhado pelos módulos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpMode {
+    // Requisições reais
+    #[default]
+    Live,
+    // Requisições reais, gravando cada resposta como fixture
+    Record,
+    // Apenas fixtures gravadas, sem acesso à rede
+    Replay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub mode: HttpMode,
+    pub fixtures_dir: String,
+    // Novas tentativas após 429, 5xx ou falha de conexão
+    pub max_retries: u32,
+    // Espera antes da primeira nova tentativa, dobrada a cada uma
+    pub backoff_ms: u64,
+    // Espera máxima entre tentativas; um `Retry-After` maior encerra as tentativas
+    pub max_backoff_secs: u64,
+    // Limite de requisições por host (ex.: "crt.sh"); hosts fora da lista não
+    // têm limite
+    pub rate_limits: BTreeMap<String, RateLimit>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            mode: HttpMode::Live,
+            fixtures_dir: "fixtures".to_string(),
+            max_retries: 3,
+            backoff_ms: 500,
+            max_backoff_secs: 30,
+            rate_limits: BTreeMap::from([
+                ("crt.sh".to_string(), RateLimit { per_second: 1.0, burst: 2 }),
+                ("api.shodan.io".to_string(), RateLimit { per_second: 1.0, burst: 1 }),
+            ]),
+        }
+    }
+}
+
+// Token bucket de um host: `per_second` requisições por segundo, com até
+// `burst` seguidas
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    // Menor taxa aceita: uma requisição a cada ~17 minutos. Taxas menores
+    // gerariam esperas que não cabem em um `Duration`.
+    pub const MIN_PER_SECOND: f64 = 0.001;
+
+    fn validate(&self, host: &str) -> Result<()> {
+        anyhow::ensure!(
+            self.per_second.is_finite() && self.per_second >= Self::MIN_PER_SECOND,
+            "http.rate_limits.\"{}\": per_second deve ser um número finito de no mínimo {}",
+            host,
+            Self::MIN_PER_SECOND
+        );
+        anyhow::ensure!(self.burst >= 1, "http.rate_limits.\"{}\": burst deve ser no mínimo 1", host);
+        Ok(())
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self { per_second: 1.0, burst: 1 }
+    }
+}
+
+// Resolução DNS dos subdomínios encontrados
+//
+// Desabilitada por padrão: é a única etapa que gera tráfego direto e alguns
+// engajamentos exigem zero interação com a infraestrutura do alvo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnsConfig {
+    pub enabled: bool,
+    // Servidores DNS (`ip` ou `ip:porta`); vazio usa a configuração do sistema
+    pub nameservers: Vec<String>,
+    pub timeout_secs: u64,
+    pub attempts: usize,
+    // Consultas simultâneas
+    pub concurrency: usize,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            nameservers: Vec::new(),
+            timeout_secs: 5,
+            attempts: 2,
+            concurrency: 20,
+        }
+    }
+}
+
+// Detecção de candidatos a subdomain takeover
+//
+// Depende dos CNAMEs obtidos pela resolução DNS, então só roda com `dns.enabled`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TakeoverConfig {
+    pub enabled: bool,
+    // Arquivo TOML de provedores; sem ele, usa a lista embutida
+    pub fingerprints_path: Option<String>,
+    // Requisita a página do subdomínio para procurar as assinaturas de "não reivindicado"
+    pub http_probe: bool,
+    pub timeout_secs: u64,
+    pub concurrency: usize,
+}
+
+impl Default for TakeoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fingerprints_path: None,
+            http_probe: true,
+            timeout_secs: 10,
+            concurrency: 10,
+        }
+    }
+}
+
+// ASN, prefixo e país dos IPs encontrados, com a classificação de CDN/hospedagem
+//
+// Consulta o serviço IP-to-ASN da Team Cymru via DNS (TXT), então também só
+// roda com `dns.enabled`. As consultas vão para a Team Cymru, não para o alvo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpInfoConfig {
+    pub enabled: bool,
+    // Zona consultada: `origin.<zona>`, `origin6.<zona>` e `AS<n>.<zona>`
+    pub zone: String,
+    // Arquivo TOML de provedores de CDN/hospedagem; sem ele, usa a lista embutida
+    pub providers_path: Option<String>,
+}
+
+impl Default for IpInfoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            zone: "asn.cymru.com".to_string(),
+            providers_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModulesConfig {
+    pub crtsh: CrtShConfig,
+    pub certspotter: CertSpotterConfig,
+    pub shodan: ShodanConfig,
+    pub wayback: WaybackConfig,
+    pub dorker: DorkerConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrtShConfig {
+    pub enabled: bool,
+    pub base_url: String,
+    pub timeout_secs: u64,
+    // Consultas simultâneas ao crt.sh
+    pub concurrency: usize,
+}
+
+impl Default for CrtShConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_url: "https://crt.sh".to_string(),
+            timeout_secs: 10,
+            concurrency: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CertSpotterConfig {
+    pub enabled: bool,
+    // Opcional; sem chave a API aceita poucas consultas por hora
+    pub api_key: String,
+    pub base_url: String,
+    pub timeout_secs: u64,
+    // Páginas de emissões consultadas por alvo
+    pub max_pages: usize,
+}
+
+impl Default for CertSpotterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            api_key: String::new(),
+            base_url: "https://api.certspotter.com".to_string(),
+            timeout_secs: 15,
+            max_pages: 5,
+        }
+    }
+}
+
+// Forma de consulta ao Shodan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShodanMode {
+    // Buscas completas, com serviços, banners e hostnames; gasta créditos de consulta
+    #[default]
+    Search,
+    // Apenas contagens agregadas por faceta (`/shodan/host/count`), sem gastar créditos
+    Facets,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShodanConfig {
+    pub enabled: bool,
+    pub api_key: String,
+    pub base_url: String,
+    pub mode: ShodanMode,
+    // Créditos de consulta que cada escaneamento pode gastar; 0 = sem limite
+    // além do saldo da conta
+    pub credit_budget: u32,
+    // Facetas resumidas no modo `facets` (ou quando os créditos acabam)
+    pub facets: Vec<String>,
+    // Tempo máximo de cada busca
+    pub search_timeout_secs: u64,
+    // Tempo máximo de cada consulta de detalhes de host
+    pub host_timeout_secs: u64,
+    // Quantidade máxima de IPs consultados em detalhe
+    pub max_host_lookups: usize,
+    // Páginas de 100 resultados seguidas em cada busca
+    pub max_pages: usize,
+    // Consulta subdomínios e registros em `/dns/domain` e nomes dos IPs em
+    // `/dns/reverse`
+    pub dns_lookups: bool,
+    // Requisições simultâneas à API
+    pub concurrency: usize,
+}
+
+impl Default for ShodanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            api_key: String::new(),
+            base_url: "https://api.shodan.io".to_string(),
+            mode: ShodanMode::Search,
+            credit_budget: 0,
+            facets: ["port", "product", "org", "country"].map(String::from).to_vec(),
+            search_timeout_secs: 10,
+            host_timeout_secs: 8,
+            max_host_lookups: 10,
+            max_pages: 3,
+            dns_lookups: true,
+            concurrency: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaybackConfig {
+    pub enabled: bool,
+    pub base_url: String,
+    pub timeout_secs: u64,
+    // Quantidade máxima de URLs retornadas pela API CDX
+    pub limit: usize,
+}
+
+impl Default for WaybackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_url: "http://web.archive.org".to_string(),
+            timeout_secs: 30,
+            limit: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DorkerConfig {
+    pub enabled: bool,
+}
+
+impl Default for DorkerConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+impl Config {
+    // Carrega a configuração
+    //
+    // Ordem de precedência: valores padrão, arquivo TOML (o caminho informado,
+    // `CORTEX_CONFIG` ou `cortex.toml` se existir) e variáveis de ambiente.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var("CORTEX_CONFIG").ok().map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+                default.exists().then_some(default)
+            });
+
+        let content = match &path {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Falha ao ler configuração em {}", path.display()))?,
+            None => String::new(),
+        };
+
+        let config = Self::from_toml(&content, std::env::vars())?;
+
+        if let Some(path) = path {
+            tracing::info!("Configuração carregada de {}", path.display());
+        }
+
+        Ok(config)
+    }
+
+    // Interpreta um documento TOML aplicando as sobrescritas de ambiente
+    //
+    // As variáveis históricas (`ENV_ALIASES` e `CORTEX_DISABLED_MODULES`) são
+    // aplicadas antes das `CORTEX__*`, que assim vencem em caso de conflito.
+    pub fn from_toml(content: &str, env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut value = toml::Value::try_from(Config::default())
+            .context("Falha ao serializar configuração padrão")?;
+
+        let file: toml::Value = toml::from_str(content).context("Configuração TOML inválida")?;
+        merge(&mut value, file);
+
+        let env: Vec<(String, String)> = env.into_iter().collect();
+
+        for (key, raw) in &env {
+            if key == DISABLED_MODULES_VAR {
+                for module in disabled_modules(raw) {
+                    set_path(&mut value, &["modules".to_string(), module.to_string(), "enabled".to_string()], "false");
+                }
+            } else if let Some((_, path)) = ENV_ALIASES.iter().find(|(alias, _)| alias == key) {
+                let segments: Vec<String> = path.split('.').map(str::to_string).collect();
+                set_path(&mut value, &segments, raw);
+            }
+        }
+
+        let mut overrides = Vec::new();
+        for (key, raw) in &env {
+            let Some(path) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let segments: Vec<String> = path.split("__").map(|s| s.to_lowercase()).collect();
+            set_path(&mut value, &segments, raw);
+            overrides.push((key, segments));
+        }
+
+        let config: Self = value.try_into().context("Configuração inválida")?;
+        for (host, limit) in &config.http.rate_limits {
+            limit.validate(host)?;
+        }
+
+        // Chaves que não existem na configuração seriam descartadas em silêncio;
+        // uma variável com erro de digitação impede a inicialização
+        let applied = toml::Value::try_from(&config).context("Falha ao serializar configuração")?;
+        for (key, segments) in overrides {
+            let known = segments
+                .iter()
+                .try_fold(&applied, |current, segment| current.get(segment.as_str()))
+                .is_some();
+            anyhow::ensure!(known, "{}: a configuração não tem a chave {}", key, segments.join("."));
+        }
+
+        Ok(config)
+    }
+}
+
+// Chaves de configuração dos módulos listados em `CORTEX_DISABLED_MODULES`
+//
+// Aceita tanto a chave (`wayback`) quanto o nome do módulo (`Wayback Machine`).
+fn disabled_modules(raw: &str) -> Vec<&'static str> {
+    raw.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+            let found = MODULE_NAMES
+                .iter()
+                .find(|(key, module)| *key == name || *module == name)
+                .map(|(key, _)| *key);
+            if found.is_none() {
+                tracing::warn!("{}: módulo desconhecido ignorado: {}", DISABLED_MODULES_VAR, name);
+            }
+            found
+        })
+        .collect()
+}
+
+// Mescla recursivamente `other` sobre `base`
+fn merge(base: &mut toml::Value, other: toml::Value) {
+    match (base, other) {
+        (toml::Value::Table(base), toml::Value::Table(other)) => {
+            for (key, value) in other {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, other) => *base = other,
+    }
+}
+
+// Define um valor no caminho indicado, convertendo-o para o tipo já existente
+//
+// Chaves ainda ausentes (ex.: um novo host em `http.rate_limits`) têm o tipo
+// deduzido do próprio valor.
+fn set_path(value: &mut toml::Value, path: &[String], raw: &str) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut current = value;
+    for segment in parents {
+        let Some(table) = current.as_table_mut() else {
+            return;
+        };
+        current = table
+            .entry(segment.clone())
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+    }
+
+    let Some(table) = current.as_table_mut() else {
+        return;
+    };
+
+    let parsed = match table.get(last) {
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        Some(toml::Value::Integer(_)) => match raw.trim().parse() {
+            Ok(n) => toml::Value::Integer(n),
+            Err(_) => toml::Value::String(raw.to_string()),
+        },
+        Some(toml::Value::Float(_)) => match raw.trim().parse() {
+            Ok(f) => toml::Value::Float(f),
+            Err(_) => toml::Value::String(raw.to_string()),
+        },
+        Some(toml::Value::Boolean(_)) => match raw.trim().parse() {
+            Ok(b) => toml::Value::Boolean(b),
+            Err(_) => toml::Value::String(raw.to_string()),
+        },
+        // Listas são informadas separadas por vírgula
+        Some(toml::Value::Array(_)) => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        Some(_) => toml::Value::String(raw.to_string()),
+        None => {
+            if let Ok(n) = raw.trim().parse() {
+                toml::Value::Integer(n)
+            } else if let Ok(f) = raw.trim().parse() {
+                toml::Value::Float(f)
+            } else {
+                toml::Value::String(raw.to_string())
+            }
+        }
+    };
+
+    table.insert(last.clone(), parsed);
+}
@@ -0,0 +1,316 @@
+use crate::{
+    jobs::ScanJob,
+    types::{Certificate, Service, Url},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Serviço cujo produto ou banner mudou entre dois escaneamentos
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceChange {
+    pub ip: String,
+    pub port: u16,
+    pub transport: String,
+    pub before: Service,
+    pub after: Service,
+}
+
+// Diferenças entre dois escaneamentos do mesmo alvo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanDiff {
+    pub target: String,
+    pub from: String,
+    pub to: String,
+    pub from_timestamp: DateTime<Utc>,
+    pub to_timestamp: DateTime<Utc>,
+    pub added_subdomains: Vec<String>,
+    pub removed_subdomains: Vec<String>,
+    pub new_services: Vec<Service>,
+    pub closed_services: Vec<Service>,
+    pub changed_services: Vec<ServiceChange>,
+    pub new_urls: Vec<Url>,
+    // Certificados comparados pelo id da fonte
+    pub new_certificates: Vec<Certificate>,
+    pub removed_certificates: Vec<Certificate>,
+}
+
+impl ScanDiff {
+    // Indica se não houve nenhuma mudança entre os escaneamentos
+    pub fn is_empty(&self) -> bool {
+        self.added_subdomains.is_empty()
+            && self.removed_subdomains.is_empty()
+            && self.new_services.is_empty()
+            && self.closed_services.is_empty()
+            && self.changed_services.is_empty()
+            && self.new_urls.is_empty()
+            && self.new_certificates.is_empty()
+            && self.removed_certificates.is_empty()
+    }
+}
+
+// Compara dois escaneamentos, do mais antigo (`from`) para o mais recente (`to`)
+pub fn diff_scans(from: &ScanJob, to: &ScanJob) -> ScanDiff {
+    let before = &from.result;
+    let after = &to.result;
+
+    // Subdomínios comparados apenas pelo nome
+    let before_names: BTreeSet<&str> = before.subdomains.iter().map(|s| s.name.as_str()).collect();
+    let after_names: BTreeSet<&str> = after.subdomains.iter().map(|s| s.name.as_str()).collect();
+
+    // Serviços identificados por (ip, porta, transporte): 53/tcp e 53/udp são distintos
+    let before_services = services_by_endpoint(&before.services);
+    let after_services = services_by_endpoint(&after.services);
+
+    let new_services = after_services
+        .iter()
+        .filter(|(endpoint, _)| !before_services.contains_key(*endpoint))
+        .map(|(_, service)| (*service).clone())
+        .collect();
+
+    let closed_services = before_services
+        .iter()
+        .filter(|(endpoint, _)| !after_services.contains_key(*endpoint))
+        .map(|(_, service)| (*service).clone())
+        .collect();
+
+    let changed_services = after_services
+        .iter()
+        .filter_map(|(endpoint, current)| {
+            let previous = before_services.get(endpoint)?;
+            if previous.service == current.service && previous.banner == current.banner {
+                return None;
+            }
+            Some(ServiceChange {
+                ip: endpoint.0.to_string(),
+                port: endpoint.1,
+                transport: endpoint.2.to_string(),
+                before: (*previous).clone(),
+                after: (*current).clone(),
+            })
+        })
+        .collect();
+
+    // URLs que passaram a constar no arquivo
+    let before_urls: BTreeSet<&str> = before.urls.iter().map(|u| u.url.as_str()).collect();
+    let mut seen_urls = BTreeSet::new();
+    let new_urls = after.urls
+        .iter()
+        .filter(|u| !before_urls.contains(u.url.as_str()) && seen_urls.insert(u.url.as_str()))
+        .cloned()
+        .collect();
+
+    let before_certificates = certificates_by_id(&before.certificates);
+    let after_certificates = certificates_by_id(&after.certificates);
+    let new_certificates = after_certificates
+        .iter()
+        .filter(|(id, _)| !before_certificates.contains_key(*id))
+        .map(|(_, certificate)| (*certificate).clone())
+        .collect();
+    let removed_certificates = before_certificates
+        .iter()
+        .filter(|(id, _)| !after_certificates.contains_key(*id))
+        .map(|(_, certificate)| (*certificate).clone())
+        .collect();
+
+    ScanDiff {
+        target: to.target.clone(),
+        from: from.id.clone(),
+        to: to.id.clone(),
+        from_timestamp: before.timestamp,
+        to_timestamp: after.timestamp,
+        added_subdomains: after_names.difference(&before_names).map(|s| s.to_string()).collect(),
+        removed_subdomains: before_names.difference(&after_names).map(|s| s.to_string()).collect(),
+        new_services,
+        closed_services,
+        changed_services,
+        new_urls,
+        new_certificates,
+        removed_certificates,
+    }
+}
+
+// Agrupa serviços por (ip, porta, transporte), mantendo um representante estável por chave
+fn services_by_endpoint(services: &[Service]) -> BTreeMap<(&str, u16, &str), &Service> {
+    let mut endpoints: BTreeMap<(&str, u16, &str), &Service> = BTreeMap::new();
+
+    for service in services {
+        endpoints
+            .entry((service.ip.as_str(), service.port, service.transport.as_str()))
+            .and_modify(|current| {
+                if service < *current {
+                    *current = service;
+                }
+            })
+            .or_insert(service);
+    }
+
+    endpoints
+}
+
+// Um certificado por id; consultas diferentes podem repetir o mesmo
+fn certificates_by_id(certificates: &[Certificate]) -> BTreeMap<&str, &Certificate> {
+    certificates.iter().map(|certificate| (certificate.id.as_str(), certificate)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{jobs::JobStatus, types::TargetResponse};
+    use chrono::TimeZone;
+    use serde_json::{json, Value};
+
+    fn job(id: &str, minute: u32, result: Value) -> ScanJob {
+        let created_at = Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap();
+        let mut result: TargetResponse = serde_json::from_value(result).unwrap();
+        result.timestamp = created_at;
+        ScanJob {
+            id: id.to_string(),
+            target: "corp.example".to_string(),
+            status: JobStatus::Completed,
+            created_at,
+            finished_at: Some(created_at),
+            result,
+        }
+    }
+
+    fn subdomain(name: &str) -> Value {
+        json!({ "name": name, "ip": null, "first_seen": null, "last_seen": null, "source": "crt.sh" })
+    }
+
+    fn service(port: u16, transport: &str, product: &str, banner: &str) -> Value {
+        json!({ "ip": "192.0.2.10", "port": port, "transport": transport, "service": product,
+                "banner": banner, "source": "Shodan" })
+    }
+
+    fn url(url: &str) -> Value {
+        json!({ "url": url, "status_code": 200, "first_seen": null, "last_seen": null, "source": "Wayback Machine" })
+    }
+
+    fn certificate(id: &str) -> Value {
+        json!({ "id": id, "serial_number": null, "issuer": "C=US, O=Let's Encrypt, CN=R3", "common_name": null,
+                "names": ["www.corp.example"], "not_before": null, "not_after": null, "logged_at": null,
+                "source": "crt.sh" })
+    }
+
+    fn scan(id: &str, minute: u32, parts: [Vec<Value>; 4]) -> ScanJob {
+        let [subdomains, services, urls, certificates] = parts;
+        job(id, minute, json!({
+            "target": "corp.example",
+            "timestamp": "2024-01-01T12:00:00Z",
+            "subdomains": subdomains,
+            "ips": [],
+            "services": services,
+            "urls": urls,
+            "dorks": [],
+            "certificates": certificates,
+            "modules": [],
+        }))
+    }
+
+    #[test]
+    fn identical_scans_have_no_differences() {
+        let parts = || [
+            vec![subdomain("www.corp.example")],
+            vec![service(443, "tcp", "nginx 1.25.3", "HTTP/1.1 200 OK")],
+            vec![url("https://www.corp.example/")],
+            vec![certificate("1")],
+        ];
+        let diff = diff_scans(&scan("a", 0, parts()), &scan("b", 1, parts()));
+
+        assert!(diff.is_empty());
+        assert_eq!((diff.from.as_str(), diff.to.as_str()), ("a", "b"));
+    }
+
+    #[test]
+    fn reports_added_and_removed_subdomains() {
+        let before = scan("a", 0, [
+            vec![subdomain("www.corp.example"), subdomain("old.corp.example")],
+            vec![], vec![], vec![],
+        ]);
+        let after = scan("b", 1, [
+            vec![subdomain("www.corp.example"), subdomain("new.corp.example"), subdomain("new.corp.example")],
+            vec![], vec![], vec![],
+        ]);
+        let diff = diff_scans(&before, &after);
+
+        assert_eq!(diff.added_subdomains, vec!["new.corp.example"]);
+        assert_eq!(diff.removed_subdomains, vec!["old.corp.example"]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn services_are_compared_per_transport() {
+        let before = scan("a", 0, [
+            vec![],
+            vec![
+                service(53, "tcp", "BIND", "version: 9.18"),
+                service(22, "tcp", "OpenSSH 9.6", "SSH-2.0-OpenSSH_9.6"),
+                service(443, "tcp", "nginx 1.25.3", "HTTP/1.1 200 OK"),
+            ],
+            vec![], vec![],
+        ]);
+        let after = scan("b", 1, [
+            vec![],
+            vec![
+                // Mesmo produto na mesma porta, mas em UDP: é um serviço novo
+                service(53, "tcp", "BIND", "version: 9.18"),
+                service(53, "udp", "BIND", "version: 9.18"),
+                service(443, "tcp", "nginx 1.27.0", "HTTP/1.1 200 OK"),
+            ],
+            vec![], vec![],
+        ]);
+        let diff = diff_scans(&before, &after);
+
+        let endpoints = |services: &[Service]| -> Vec<(u16, String)> {
+            services.iter().map(|s| (s.port, s.transport.clone())).collect()
+        };
+        assert_eq!(endpoints(&diff.new_services), vec![(53, "udp".to_string())]);
+        assert_eq!(endpoints(&diff.closed_services), vec![(22, "tcp".to_string())]);
+
+        assert_eq!(diff.changed_services.len(), 1);
+        let change = &diff.changed_services[0];
+        assert_eq!((change.ip.as_str(), change.port, change.transport.as_str()), ("192.0.2.10", 443, "tcp"));
+        assert_eq!(change.before.service, "nginx 1.25.3");
+        assert_eq!(change.after.service, "nginx 1.27.0");
+    }
+
+    #[test]
+    fn banner_changes_count_as_changed_services() {
+        let before = scan("a", 0, [vec![], vec![service(22, "tcp", "OpenSSH", "SSH-2.0-OpenSSH_9.6")], vec![], vec![]]);
+        let after = scan("b", 1, [vec![], vec![service(22, "tcp", "OpenSSH", "SSH-2.0-OpenSSH_9.7")], vec![], vec![]]);
+        let diff = diff_scans(&before, &after);
+
+        assert!(diff.new_services.is_empty() && diff.closed_services.is_empty());
+        assert_eq!(diff.changed_services.len(), 1);
+    }
+
+    #[test]
+    fn only_newly_archived_urls_are_reported() {
+        let before = scan("a", 0, [vec![], vec![], vec![url("https://www.corp.example/")], vec![]]);
+        let after = scan("b", 1, [
+            vec![],
+            vec![],
+            vec![url("https://www.corp.example/login"), url("https://www.corp.example/login")],
+            vec![],
+        ]);
+        let diff = diff_scans(&before, &after);
+
+        // URLs que saíram do arquivo não são relatadas
+        let urls: Vec<&str> = diff.new_urls.iter().map(|u| u.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://www.corp.example/login"]);
+    }
+
+    #[test]
+    fn reports_new_and_removed_certificates() {
+        let before = scan("a", 0, [vec![], vec![], vec![], vec![certificate("1"), certificate("2")]]);
+        let after = scan("b", 1, [vec![], vec![], vec![], vec![certificate("2"), certificate("3"), certificate("3")]]);
+        let diff = diff_scans(&before, &after);
+
+        let ids = |certificates: &[Certificate]| -> Vec<String> {
+            certificates.iter().map(|c| c.id.clone()).collect()
+        };
+        assert_eq!(ids(&diff.new_certificates), vec!["3"]);
+        assert_eq!(ids(&diff.removed_certificates), vec!["1"]);
+    }
+}
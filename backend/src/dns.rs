@@ -0,0 +1,268 @@
+use crate::{config::DnsConfig, types::{IpInfo, Subdomain, TargetResponse}};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use hickory_resolver::{
+    config::{LookupIpStrategy, NameServerConfig, Protocol, ResolverConfig, ResolverOpts},
+    error::ResolveErrorKind,
+    proto::{op::ResponseCode, rr::RecordType},
+    TokioAsyncResolver,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+// Porta usada quando o servidor DNS é informado sem porta
+const DEFAULT_DNS_PORT: u16 = 53;
+
+// Limite de saltos ao seguir CNAMEs que não resolvem
+const MAX_CNAME_DEPTH: usize = 8;
+
+// Resultado da resolução de um nome
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolution {
+    // Endereços A/AAAA, com IPv4 primeiro
+    pub addresses: Vec<IpAddr>,
+    // Cadeia de CNAMEs seguida até os endereços, sem o nome consultado
+    pub cnames: Vec<String>,
+    // A cadeia termina em um nome inexistente (NXDOMAIN)
+    pub dangling: bool,
+}
+
+// Resolvedor usado para enriquecer os subdomínios encontrados
+//
+// É a única etapa que gera tráfego direto (consultas DNS), por isso só é
+// construído quando `dns.enabled` está ativo na configuração.
+pub struct DnsResolver {
+    resolver: TokioAsyncResolver,
+    concurrency: usize,
+}
+
+impl DnsResolver {
+    pub fn from_config(config: &DnsConfig) -> Result<Self> {
+        let (resolver_config, mut opts) = if config.nameservers.is_empty() {
+            hickory_resolver::system_conf::read_system_conf()
+                .context("Falha ao ler a configuração DNS do sistema")?
+        } else {
+            let mut resolver_config = ResolverConfig::new();
+            for nameserver in &config.nameservers {
+                let addr = parse_nameserver(nameserver)
+                    .with_context(|| format!("Servidor DNS inválido: {}", nameserver))?;
+                resolver_config.add_name_server(NameServerConfig::new(addr, Protocol::Udp));
+            }
+            (resolver_config, ResolverOpts::default())
+        };
+
+        opts.timeout = Duration::from_secs(config.timeout_secs);
+        opts.attempts = config.attempts;
+        opts.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+        opts.preserve_intermediates = true;
+
+        Ok(Self {
+            resolver: TokioAsyncResolver::tokio(resolver_config, opts),
+            concurrency: config.concurrency.max(1),
+        })
+    }
+
+    // Resolve A/AAAA de um nome, registrando os CNAMEs intermediários
+    pub async fn resolve(&self, name: &str) -> Option<Resolution> {
+        let fqdn = format!("{}.", name.trim_end_matches('.'));
+
+        let lookup = match self.resolver.lookup_ip(fqdn.as_str()).await {
+            Ok(lookup) => lookup,
+            Err(e) => {
+                // Um CNAME para um nome inexistente também resulta em NXDOMAIN
+                if let ResolveErrorKind::NoRecordsFound { response_code: ResponseCode::NXDomain, .. } = e.kind() {
+                    let cnames = self.cname_chain(name).await;
+                    if !cnames.is_empty() {
+                        return Some(Resolution { addresses: Vec::new(), cnames, dangling: true });
+                    }
+                }
+                tracing::debug!("DNS: falha ao resolver {}: {}", name, e);
+                return None;
+            }
+        };
+
+        let mut addresses: Vec<IpAddr> = lookup.iter().collect();
+        addresses.sort_by_key(|addr| (addr.is_ipv6(), *addr));
+        addresses.dedup();
+
+        // Segue a cadeia a partir do nome consultado
+        let aliases: HashMap<String, String> = lookup
+            .as_lookup()
+            .records()
+            .iter()
+            .filter(|record| record.record_type() == RecordType::CNAME)
+            .filter_map(|record| {
+                let target = record.data()?.as_cname()?;
+                Some((normalize(&record.name().to_string()), normalize(&target.to_string())))
+            })
+            .collect();
+
+        let mut cnames = Vec::new();
+        let mut current = normalize(name);
+        while let Some(next) = aliases.get(&current) {
+            if cnames.contains(next) {
+                break;
+            }
+            cnames.push(next.clone());
+            current = next.clone();
+        }
+
+        Some(Resolution { addresses, cnames, dangling: false })
+    }
+
+    // Segue os CNAMEs um a um, consultando apenas registros CNAME
+    async fn cname_chain(&self, name: &str) -> Vec<String> {
+        let mut cnames = Vec::new();
+        let mut current = normalize(name);
+
+        while cnames.len() < MAX_CNAME_DEPTH {
+            let Ok(lookup) = self.resolver.lookup(format!("{}.", current), RecordType::CNAME).await else {
+                break;
+            };
+            let next = lookup
+                .record_iter()
+                .filter(|record| normalize(&record.name().to_string()) == current)
+                .find_map(|record| record.data()?.as_cname().map(|target| normalize(&target.to_string())));
+
+            match next {
+                Some(next) if !cnames.contains(&next) => {
+                    cnames.push(next.clone());
+                    current = next;
+                }
+                _ => break,
+            }
+        }
+
+        cnames
+    }
+
+    // Consultas simultâneas permitidas
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    // Registros TXT de um nome, com os segmentos de cada registro concatenados
+    pub async fn txt(&self, name: &str) -> Vec<String> {
+        let fqdn = format!("{}.", name.trim_end_matches('.'));
+
+        match self.resolver.txt_lookup(fqdn.as_str()).await {
+            Ok(lookup) => lookup
+                .iter()
+                .map(|txt| txt.txt_data().iter().map(|data| String::from_utf8_lossy(data)).collect())
+                .collect(),
+            Err(e) => {
+                tracing::debug!("DNS: sem TXT para {}: {}", name, e);
+                Vec::new()
+            }
+        }
+    }
+
+    // Nomes reversos (PTR) de um endereço
+    pub async fn reverse(&self, ip: IpAddr) -> Vec<String> {
+        match self.resolver.reverse_lookup(ip).await {
+            Ok(lookup) => {
+                let mut names: Vec<String> = lookup.iter().map(|name| normalize(&name.to_string())).collect();
+                names.sort_unstable();
+                names.dedup();
+                names
+            }
+            Err(e) => {
+                tracing::debug!("DNS: sem PTR para {}: {}", ip, e);
+                Vec::new()
+            }
+        }
+    }
+
+    // Consulta os PTRs dos endereços em paralelo, agrupando os nomes por IP
+    pub async fn reverse_all(&self, ips: Vec<IpAddr>) -> Vec<Subdomain> {
+        let lookups: Vec<_> = ips
+            .into_iter()
+            .map(|ip| async move { (ip, self.reverse(ip).await) })
+            .collect();
+
+        let mut results: Vec<(IpAddr, Vec<String>)> = stream::iter(lookups)
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        results.sort_unstable();
+
+        results
+            .into_iter()
+            .flat_map(|(ip, names)| {
+                names.into_iter().map(move |name| Subdomain {
+                    name,
+                    ip: Some(ip.to_string()),
+                    cnames: Vec::new(),
+                    wildcard: false,
+                    first_seen: None,
+                    last_seen: None,
+                    issuer: None,
+                    certificates: Vec::new(),
+                    records: Vec::new(),
+                    source: "PTR".to_string(),
+                    sources: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    // Preenche IPs e CNAMEs dos subdomínios e acrescenta os endereços aos IPs do alvo
+    //
+    // Retorna as resoluções por nome, usadas pelas etapas seguintes.
+    pub async fn enrich(&self, response: &mut TargetResponse) -> HashMap<String, Resolution> {
+        let mut names: Vec<String> = response.subdomains.iter().map(|s| s.name.clone()).collect();
+        names.sort_unstable();
+        names.dedup();
+
+        let resolutions: HashMap<String, Resolution> = stream::iter(names)
+            .map(|name| async move {
+                let resolution = self.resolve(&name).await;
+                (name, resolution)
+            })
+            .buffer_unordered(self.concurrency)
+            .filter_map(|(name, resolution)| async move { resolution.map(|r| (name, r)) })
+            .collect()
+            .await;
+
+        for subdomain in &mut response.subdomains {
+            let Some(resolution) = resolutions.get(&subdomain.name) else {
+                continue;
+            };
+            if subdomain.ip.is_none() {
+                subdomain.ip = resolution.addresses.first().map(IpAddr::to_string);
+            }
+            if subdomain.cnames.is_empty() {
+                subdomain.cnames = resolution.cnames.clone();
+            }
+        }
+
+        response.ips.extend(
+            resolutions
+                .values()
+                .flat_map(|resolution| resolution.addresses.iter().map(|ip| IpInfo::new(ip.to_string()))),
+        );
+
+        tracing::info!(
+            "DNS: {} de {} subdomínios resolvidos para {}",
+            resolutions.len(),
+            response.subdomains.len(),
+            response.target
+        );
+
+        resolutions
+    }
+}
+
+// Aceita `1.1.1.1`, `1.1.1.1:53`, `::1` ou `[::1]:5353`
+fn parse_nameserver(value: &str) -> Result<SocketAddr> {
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    let ip: IpAddr = value.parse()?;
+    Ok(SocketAddr::new(ip, DEFAULT_DNS_PORT))
+}
+
+fn normalize(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase()
+}
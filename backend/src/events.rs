@@ -0,0 +1,164 @@
+use crate::{
+    jobs::ScanJob,
+    scope::Scope,
+    types::{AppState, Dork, Finding, IpInfo, ModuleReport, Service, Subdomain, TargetResponse, Url},
+};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+// Capacidade do canal de eventos entre o escaneamento e o cliente
+const EVENT_BUFFER: usize = 256;
+
+// Origem dos eventos do enriquecimento, que não vêm de um módulo
+const DNS_EVENTS: &str = "DNS";
+const TAKEOVER_EVENTS: &str = "takeover";
+
+// Eventos emitidos durante um escaneamento transmitido ao vivo
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScanEvent {
+    ModuleStarted { module: String },
+    Subdomain { module: String, subdomain: Subdomain },
+    Ip { module: String, ip: IpInfo },
+    Service { module: String, service: Service },
+    Url { module: String, url: Url },
+    Dork { module: String, dork: Dork },
+    Finding { module: String, finding: Finding },
+    ModuleFinished { report: ModuleReport },
+    ScanComplete { id: String, result: TargetResponse },
+}
+
+impl ScanEvent {
+    // Nome do evento (usado como `event:` no SSE)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScanEvent::ModuleStarted { .. } => "module_started",
+            ScanEvent::Subdomain { .. } => "subdomain",
+            ScanEvent::Ip { .. } => "ip",
+            ScanEvent::Service { .. } => "service",
+            ScanEvent::Url { .. } => "url",
+            ScanEvent::Dork { .. } => "dork",
+            ScanEvent::Finding { .. } => "finding",
+            ScanEvent::ModuleFinished { .. } => "module_finished",
+            ScanEvent::ScanComplete { .. } => "scan_complete",
+        }
+    }
+}
+
+// Inicia um escaneamento em segundo plano e retorna o fluxo de eventos
+//
+// O escaneamento é interrompido assim que o cliente deixa de consumir o fluxo.
+pub fn stream_scan(state: Arc<AppState>, scope: Scope) -> ReceiverStream<ScanEvent> {
+    let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+
+    tokio::spawn(async move {
+        let registry = &state.registry;
+        let target = scope.target().history_key();
+        let mut response = TargetResponse::new(&target);
+
+        // Cada módulo anuncia o início no momento em que passa a rodar
+        let mut modules: FuturesUnordered<_> = registry
+            .modules()
+            .iter()
+            .map(|module| {
+                let (tx, scope) = (&tx, &scope);
+                async move {
+                    let started = ScanEvent::ModuleStarted { module: module.name().to_string() };
+                    tx.send(started).await.ok()?;
+                    Some(registry.run_module(module.as_ref(), scope).await)
+                }
+            })
+            .collect();
+
+        while let Some(outcome) = modules.next().await {
+            let Some((report, data)) = outcome else {
+                tracing::info!("Cliente desconectado, interrompendo escaneamento de {}", target);
+                return;
+            };
+            let module = report.name.clone();
+            let mut events = Vec::new();
+
+            if let Some(data) = &data {
+                events.extend(data.subdomains.iter().cloned().map(|subdomain| {
+                    ScanEvent::Subdomain { module: module.clone(), subdomain }
+                }));
+                events.extend(data.ips.iter().map(|ip| {
+                    ScanEvent::Ip { module: module.clone(), ip: IpInfo::new(ip.clone()) }
+                }));
+                events.extend(data.services.iter().cloned().map(|service| {
+                    ScanEvent::Service { module: module.clone(), service }
+                }));
+                events.extend(data.urls.iter().cloned().map(|url| {
+                    ScanEvent::Url { module: module.clone(), url }
+                }));
+                events.extend(data.dorks.iter().cloned().map(|dork| {
+                    ScanEvent::Dork { module: module.clone(), dork }
+                }));
+            }
+            events.push(ScanEvent::ModuleFinished { report: report.clone() });
+
+            for event in events {
+                if tx.send(event).await.is_err() {
+                    tracing::info!("Cliente desconectado, interrompendo escaneamento de {}", target);
+                    return;
+                }
+            }
+
+            response.record(report, data);
+        }
+
+        drop(modules);
+
+        let mut before = response.clone();
+        before.dedup();
+        registry.enrich(&scope, &mut response).await;
+        registry.finalize(&mut response);
+
+        for event in enrichment_events(&before, &response) {
+            if tx.send(event).await.is_err() {
+                tracing::info!("Cliente desconectado, interrompendo escaneamento de {}", target);
+                return;
+            }
+        }
+
+        let job = ScanJob::from_result(response);
+        state.persist(&job).await;
+
+        let _ = tx.send(ScanEvent::ScanComplete { id: job.id, result: job.result }).await;
+    });
+
+    ReceiverStream::new(rx)
+}
+
+// Itens que o enriquecimento acrescentou ou alterou: subdomínios resolvidos ou
+// vindos de PTR, IPs com dados de rede e candidatos a takeover
+fn enrichment_events(before: &TargetResponse, after: &TargetResponse) -> Vec<ScanEvent> {
+    let subdomains: BTreeMap<&str, &Subdomain> = before.subdomains.iter().map(|s| (s.name.as_str(), s)).collect();
+    let ips: BTreeMap<&str, &IpInfo> = before.ips.iter().map(|info| (info.ip.as_str(), info)).collect();
+    let findings: BTreeSet<&Finding> = before.findings.iter().collect();
+
+    let mut events = Vec::new();
+    events.extend(
+        after.subdomains
+            .iter()
+            .filter(|subdomain| subdomains.get(subdomain.name.as_str()) != Some(subdomain))
+            .map(|subdomain| ScanEvent::Subdomain { module: DNS_EVENTS.to_string(), subdomain: subdomain.clone() }),
+    );
+    events.extend(
+        after.ips
+            .iter()
+            .filter(|info| ips.get(info.ip.as_str()) != Some(info))
+            .map(|info| ScanEvent::Ip { module: DNS_EVENTS.to_string(), ip: info.clone() }),
+    );
+    events.extend(
+        after.findings
+            .iter()
+            .filter(|finding| !findings.contains(finding))
+            .map(|finding| ScanEvent::Finding { module: TAKEOVER_EVENTS.to_string(), finding: finding.clone() }),
+    );
+    events
+}
@@ -0,0 +1,725 @@
+use crate::config::{HttpConfig, HttpMode, RateLimit};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Parâmetros de query com credenciais, removidos das fixtures
+const SECRET_PARAMS: &[&str] = &["key", "api_key", "apikey", "token"];
+
+// Resposta HTTP já lida por completo
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub body: String,
+    pub host: String,
+    // Tentativas feitas até obter esta resposta
+    pub attempts: u32,
+    // Espera pedida pelo servidor no cabeçalho `Retry-After`
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    // Converte uma resposta de erro em `HttpError`, mantendo as de sucesso
+    pub fn error_for_status(self) -> Result<Self, HttpError> {
+        if self.is_success() {
+            return Ok(self);
+        }
+
+        Err(HttpError {
+            kind: if self.status == StatusCode::TOO_MANY_REQUESTS {
+                HttpErrorKind::RateLimited
+            } else {
+                HttpErrorKind::Status
+            },
+            host: self.host,
+            status: Some(self.status.as_u16()),
+            attempts: self.attempts,
+            retry_after_secs: self.retry_after.map(|wait| wait.as_secs()),
+        })
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.body).context("Corpo da resposta não é um JSON válido")
+    }
+}
+
+// Tipo de falha de uma requisição HTTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpErrorKind {
+    // 429 mesmo após as novas tentativas
+    RateLimited,
+    // Demais status de erro
+    Status,
+    Timeout,
+    Connect,
+    // Outras falhas de rede ou de leitura do corpo
+    Transport,
+}
+
+// Falha HTTP estruturada, repassada até o relatório do módulo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpError {
+    pub kind: HttpErrorKind,
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            HttpErrorKind::RateLimited => write!(f, "{} limitou as requisições (status 429)", self.host)?,
+            HttpErrorKind::Status => write!(
+                f,
+                "{} retornou status {}",
+                self.host,
+                self.status.map_or_else(|| "?".to_string(), |status| status.to_string())
+            )?,
+            HttpErrorKind::Timeout => write!(f, "Tempo esgotado ao consultar {}", self.host)?,
+            HttpErrorKind::Connect => write!(f, "Falha de conexão com {}", self.host)?,
+            HttpErrorKind::Transport => write!(f, "Falha de rede ao consultar {}", self.host)?,
+        }
+        if self.attempts > 1 {
+            write!(f, " após {} tentativas", self.attempts)?;
+        }
+        if let Some(secs) = self.retry_after_secs {
+            write!(f, "; o servidor pediu {}s de espera", secs)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+// Vagas de requisição de um host
+//
+// Token bucket em que cada requisição reserva uma vaga, mesmo que o saldo fique
+// negativo, e espera o tempo de repô-la; uma pausa pedida pelo servidor vale
+// para todas as requisições ao host.
+struct HostLimit {
+    limit: Option<RateLimit>,
+    tokens: f64,
+    updated: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl HostLimit {
+    fn new(limit: Option<RateLimit>, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.map_or(0.0, |limit| limit.burst.max(1) as f64),
+            updated: now,
+            paused_until: None,
+        }
+    }
+
+    // Reserva uma vaga e devolve quanto esperar por ela
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let mut wait = self.paused_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+
+        if let Some(limit) = self.limit.filter(|limit| limit.per_second > 0.0) {
+            let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst.max(1) as f64);
+            self.updated = now;
+            self.tokens -= 1.0;
+            if self.tokens < 0.0 {
+                wait = wait.max(Duration::from_secs_f64(-self.tokens / limit.per_second));
+            }
+        }
+
+        wait
+    }
+}
+
+// Limites por host compartilhados por todos os módulos
+struct RateLimiter {
+    limits: BTreeMap<String, RateLimit>,
+    hosts: Mutex<HashMap<String, HostLimit>>,
+}
+
+impl RateLimiter {
+    fn new(limits: BTreeMap<String, RateLimit>) -> Self {
+        Self { limits, hosts: Mutex::default() }
+    }
+
+    // Aguarda a vez de uma requisição ao host
+    async fn acquire(&self, host: &str) {
+        let wait = {
+            let now = Instant::now();
+            let mut hosts = self.hosts.lock().unwrap();
+            hosts
+                .entry(host.to_string())
+                .or_insert_with(|| HostLimit::new(self.limits.get(host).copied(), now))
+                .reserve(now)
+        };
+
+        if !wait.is_zero() {
+            tracing::debug!("HTTP: aguardando {}ms pelo limite de {}", wait.as_millis(), host);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Suspende as requisições ao host pelo tempo pedido em `Retry-After`
+    fn pause(&self, host: &str, duration: Duration) {
+        let now = Instant::now();
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts
+            .entry(host.to_string())
+            .or_insert_with(|| HostLimit::new(self.limits.get(host).copied(), now));
+        state.paused_until = state.paused_until.max(Some(now + duration));
+    }
+}
+
+// Novas tentativas com espera exponencial
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    // Espera antes da nova tentativa seguinte à tentativa `attempt` (1, 2, ...)
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+// Resposta gravada em disco para reprodução offline
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+// Cliente HTTP compartilhado pelos módulos
+//
+// No modo `record` as respostas reais são gravadas como fixtures e no modo
+// `replay` são lidas do disco, sem nenhum acesso à rede. Fora do `replay`, as
+// requisições respeitam os limites por host e são repetidas após 429, 5xx e
+// falhas de conexão.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    mode: Arc<Mode>,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
+}
+
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Live,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::from_config(&HttpConfig::default())
+    }
+}
+
+impl HttpClient {
+    pub fn from_config(config: &HttpConfig) -> Self {
+        let dir = PathBuf::from(&config.fixtures_dir);
+        let mode = match config.mode {
+            HttpMode::Live => Mode::Live,
+            HttpMode::Record => Mode::Record(dir),
+            HttpMode::Replay => Mode::Replay(dir),
+        };
+
+        Self::with_mode(config, mode)
+    }
+
+    // Cliente que grava cada resposta no diretório indicado
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self::with_mode(&HttpConfig::default(), Mode::Record(dir.into()))
+    }
+
+    // Cliente que apenas reproduz respostas gravadas no diretório indicado
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self::with_mode(&HttpConfig::default(), Mode::Replay(dir.into()))
+    }
+
+    fn with_mode(config: &HttpConfig, mode: Mode) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            mode: Arc::new(mode),
+            retry: RetryPolicy {
+                max_retries: config.max_retries,
+                backoff: Duration::from_millis(config.backoff_ms),
+                max_backoff: Duration::from_secs(config.max_backoff_secs),
+            },
+            limiter: Arc::new(RateLimiter::new(config.rate_limits.clone())),
+        }
+    }
+
+    // Mesmo cliente, com os mesmos limites por host, mas sem novas tentativas
+    //
+    // Para sondagens em que uma falha já é a resposta e repetir só gera ruído.
+    pub fn without_retries(&self) -> Self {
+        let mut client = self.clone();
+        client.retry.max_retries = 0;
+        client
+    }
+
+    // Realiza uma requisição GET com o tempo máximo indicado
+    pub async fn get(&self, url: &str, timeout: Duration) -> Result<HttpResponse> {
+        self.get_with_headers(url, &[], timeout).await
+    }
+
+    // Realiza uma requisição GET com cabeçalhos adicionais
+    //
+    // Os cabeçalhos não fazem parte da fixture, então credenciais enviadas por
+    // eles nunca são gravadas.
+    pub async fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        timeout: Duration,
+    ) -> Result<HttpResponse> {
+        match &*self.mode {
+            Mode::Live => self.fetch(url, headers, timeout).await,
+            Mode::Record(dir) => {
+                let response = self.fetch(url, headers, timeout).await?;
+                save_fixture(dir, url, &response)?;
+                Ok(response)
+            }
+            Mode::Replay(dir) => load_fixture(dir, url),
+        }
+    }
+
+    // Requisição real, com limite por host e novas tentativas
+    //
+    // Status de erro voltam como resposta (`HttpResponse::error_for_status`);
+    // falhas de rede voltam como `HttpError`, com o erro original como causa.
+    async fn fetch(&self, url: &str, headers: &[(&str, &str)], timeout: Duration) -> Result<HttpResponse> {
+        let host = host_of(url);
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let retries_left = attempt <= self.retry.max_retries;
+            self.limiter.acquire(&host).await;
+
+            match self.fetch_once(url, headers, timeout, &host, attempt).await {
+                Ok(response) if !retries_left || !is_retryable(response.status) => return Ok(response),
+                Ok(response) => match response.retry_after {
+                    Some(wait) if wait > self.retry.max_backoff => {
+                        tracing::warn!(
+                            "HTTP: {} pediu {}s de espera (status {}), acima do máximo de {}s",
+                            host,
+                            wait.as_secs(),
+                            response.status,
+                            self.retry.max_backoff.as_secs()
+                        );
+                        return Ok(response);
+                    }
+                    // A pausa vale para todas as requisições ao host
+                    Some(wait) => {
+                        tracing::warn!(
+                            "HTTP: {} retornou status {}, nova tentativa em {}ms ({} de {})",
+                            host,
+                            response.status,
+                            wait.as_millis(),
+                            attempt,
+                            self.retry.max_retries
+                        );
+                        self.limiter.pause(&host, wait);
+                    }
+                    None => {
+                        let wait = self.retry.backoff(attempt);
+                        tracing::warn!(
+                            "HTTP: {} retornou status {}, nova tentativa em {}ms ({} de {})",
+                            host,
+                            response.status,
+                            wait.as_millis(),
+                            attempt,
+                            self.retry.max_retries
+                        );
+                        tokio::time::sleep(wait).await;
+                    }
+                },
+                Err(e) if retries_left && e.is_connect() => {
+                    let wait = self.retry.backoff(attempt);
+                    tracing::warn!(
+                        "HTTP: falha de conexão com {}, nova tentativa em {}ms ({} de {}): {}",
+                        host,
+                        wait.as_millis(),
+                        attempt,
+                        self.retry.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                Err(e) => {
+                    let kind = if e.is_timeout() {
+                        HttpErrorKind::Timeout
+                    } else if e.is_connect() {
+                        HttpErrorKind::Connect
+                    } else {
+                        HttpErrorKind::Transport
+                    };
+                    let error = HttpError { kind, host, status: None, attempts: attempt, retry_after_secs: None };
+                    return Err(anyhow::Error::new(e).context(error));
+                }
+            }
+        }
+    }
+
+    async fn fetch_once(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        timeout: Duration,
+        host: &str,
+        attempt: u32,
+    ) -> reqwest::Result<HttpResponse> {
+        let mut request = self.client.get(url).timeout(timeout);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = request.send().await?;
+
+        let status = response.status();
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+        let body = response.text().await?;
+
+        Ok(HttpResponse { status, body, host: host.to_string(), attempts: attempt, retry_after })
+    }
+}
+
+// Respostas que valem uma nova tentativa
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// `Retry-After` em segundos ou como data HTTP
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+// Host da URL, chave dos limites de requisição
+fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+// Remove credenciais da URL antes de gravá-la ou usá-la como chave
+fn redact(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+
+    if parsed.query().is_none() {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if SECRET_PARAMS.contains(&key.as_ref()) {
+                "REDACTED".to_string()
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+
+    // Reescreve a query apenas quando há credenciais, preservando a URL original
+    if pairs.iter().all(|(key, _)| !SECRET_PARAMS.contains(&key.as_str())) {
+        return url.to_string();
+    }
+
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+// Caminho da fixture de uma requisição: URL legível seguida de um hash estável
+fn fixture_path(dir: &Path, url: &str) -> PathBuf {
+    let key = format!("GET {}", redact(url));
+
+    // FNV-1a 64 bits, estável entre versões do compilador
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    let readable: String = redact(url)
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' })
+        .take(80)
+        .collect();
+
+    dir.join(format!("{}-{:016x}.json", readable, hash))
+}
+
+fn save_fixture(dir: &Path, url: &str, response: &HttpResponse) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Falha ao criar diretório de fixtures {}", dir.display()))?;
+
+    let (json, text) = match serde_json::from_str::<Value>(&response.body) {
+        Ok(json) => (Some(json), None),
+        Err(_) => (None, Some(response.body.clone())),
+    };
+
+    let fixture = Fixture {
+        method: "GET".to_string(),
+        url: redact(url),
+        status: response.status.as_u16(),
+        json,
+        text,
+    };
+
+    let path = fixture_path(dir, url);
+    std::fs::write(&path, serde_json::to_string_pretty(&fixture)?)
+        .with_context(|| format!("Falha ao gravar fixture {}", path.display()))?;
+
+    tracing::debug!("Fixture gravada em {}", path.display());
+
+    Ok(())
+}
+
+fn load_fixture(dir: &Path, url: &str) -> Result<HttpResponse> {
+    let path = fixture_path(dir, url);
+
+    let content = std::fs::read_to_string(&path).map_err(|_| {
+        anyhow!("Fixture não encontrada para GET {} ({})", redact(url), path.display())
+    })?;
+
+    let fixture: Fixture = serde_json::from_str(&content)
+        .with_context(|| format!("Fixture inválida: {}", path.display()))?;
+
+    let body = match (fixture.json, fixture.text) {
+        (Some(json), _) => json.to_string(),
+        (None, Some(text)) => text,
+        (None, None) => String::new(),
+    };
+
+    Ok(HttpResponse {
+        status: StatusCode::from_u16(fixture.status)
+            .with_context(|| format!("Status inválido na fixture {}", path.display()))?,
+        body,
+        host: host_of(url),
+        attempts: 1,
+        retry_after: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::{header, StatusCode as AxumStatus}, response::IntoResponse, routing::get, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    async fn spawn(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn quick_retries(max_retries: u32) -> HttpClient {
+        HttpClient::from_config(&HttpConfig { max_retries, backoff_ms: 5, max_backoff_secs: 5, ..Default::default() })
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z").unwrap().with_timezone(&Utc);
+
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("amanhã", now), None);
+    }
+
+    #[test]
+    fn token_bucket_spaces_requests_after_the_burst() {
+        let start = Instant::now();
+        let mut host = HostLimit::new(Some(RateLimit { per_second: 2.0, burst: 2 }), start);
+
+        assert_eq!(host.reserve(start), Duration::ZERO);
+        assert_eq!(host.reserve(start), Duration::ZERO);
+        assert_eq!(host.reserve(start), Duration::from_millis(500));
+        assert_eq!(host.reserve(start), Duration::from_secs(1));
+
+        // Após repor o saldo, uma pausa do servidor ainda vale
+        let later = start + Duration::from_secs(10);
+        host.paused_until = Some(later + Duration::from_secs(3));
+        assert_eq!(host.reserve(later), Duration::from_secs(3));
+
+        // Hosts sem limite só esperam pausas
+        let mut free = HostLimit::new(None, start);
+        assert_eq!(free.reserve(start), Duration::ZERO);
+        assert_eq!(free.reserve(start), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn retries_throttled_and_failing_responses() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/flaky",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 => (AxumStatus::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")], "devagar").into_response(),
+                        1 => (AxumStatus::SERVICE_UNAVAILABLE, "fora do ar").into_response(),
+                        _ => "ok".into_response(),
+                    }
+                }
+            }),
+        );
+        let base_url = spawn(app).await;
+
+        let response = quick_retries(3).get(&format!("{}/flaky", base_url), Duration::from_secs(5)).await.unwrap();
+        assert!(response.is_success());
+        assert_eq!((response.body.as_str(), response.attempts), ("ok", 3));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_with_a_structured_error() {
+        let app = Router::new()
+            .route("/busy", get(|| async { (AxumStatus::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")]) }))
+            .route("/later", get(|| async { (AxumStatus::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "3600")]) }))
+            .route("/missing", get(|| async { AxumStatus::NOT_FOUND }));
+        let base_url = spawn(app).await;
+        let http = quick_retries(2);
+        let timeout = Duration::from_secs(5);
+
+        let error = http.get(&format!("{}/busy", base_url), timeout).await.unwrap().error_for_status().unwrap_err();
+        assert_eq!(error.kind, HttpErrorKind::RateLimited);
+        assert_eq!((error.status, error.attempts, error.retry_after_secs), (Some(429), 3, Some(0)));
+        assert_eq!(error.host, "127.0.0.1");
+
+        // Espera acima do máximo e erros do cliente não são repetidos
+        let later = http.get(&format!("{}/later", base_url), timeout).await.unwrap();
+        assert_eq!((later.attempts, later.retry_after), (1, Some(Duration::from_secs(3600))));
+        let missing = http.get(&format!("{}/missing", base_url), timeout).await.unwrap();
+        assert_eq!(missing.attempts, 1);
+        assert_eq!(missing.error_for_status().unwrap_err().to_string(), "127.0.0.1 retornou status 404");
+
+        // Falhas de rede carregam o erro estruturado e a causa original
+        let error = http.get("http://127.0.0.1:9/", timeout).await.unwrap_err();
+        let http_error = error.downcast_ref::<HttpError>().unwrap();
+        assert_eq!((http_error.kind, http_error.attempts), (HttpErrorKind::Connect, 3));
+    }
+
+    #[tokio::test]
+    async fn probes_without_retries_make_a_single_attempt() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/down",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    AxumStatus::SERVICE_UNAVAILABLE
+                }
+            }),
+        );
+        let base_url = spawn(app).await;
+
+        let response = quick_retries(3)
+            .without_retries()
+            .get(&format!("{}/down", base_url), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!((response.status.as_u16(), response.attempts), (503, 1));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn redacts_credentials_from_urls() {
+        assert_eq!(
+            redact("https://api.shodan.io/shodan/host/1.2.3.4?key=secret"),
+            "https://api.shodan.io/shodan/host/1.2.3.4?key=REDACTED"
+        );
+        assert_eq!(
+            redact("https://crt.sh/?q=%.corp.example&output=json"),
+            "https://crt.sh/?q=%.corp.example&output=json"
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_responses_replay_offline() {
+        let app = Router::new()
+            .route("/data", get(|| async { r#"{"ok":true}"# }))
+            .route("/text", get(|| async { "plain body" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = std::env::temp_dir().join(format!("cortex-fixtures-{}", uuid::Uuid::new_v4()));
+        let timeout = Duration::from_secs(5);
+
+        let recorder = HttpClient::record(&dir);
+        let json_url = format!("http://{}/data?key=secret", addr);
+        let text_url = format!("http://{}/text", addr);
+        recorder.get(&json_url, timeout).await.unwrap();
+        recorder.get(&text_url, timeout).await.unwrap();
+
+        // Sem o servidor, apenas as fixtures respondem
+        server.abort();
+
+        let replayer = HttpClient::replay(&dir);
+        let json = replayer.get(&json_url.replace("secret", "other"), timeout).await.unwrap();
+        assert!(json.is_success());
+        assert_eq!(json.json::<Value>().unwrap(), serde_json::json!({ "ok": true }));
+
+        let text = replayer.get(&text_url, timeout).await.unwrap();
+        assert_eq!(text.body, "plain body");
+
+        assert!(replayer.get(&format!("http://{}/missing", addr), timeout).await.is_err());
+
+        let recorded = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(recorded, 2);
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(!content.contains("secret"));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
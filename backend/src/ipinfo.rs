@@ -0,0 +1,243 @@
+use crate::{
+    config::IpInfoConfig,
+    dns::DnsResolver,
+    types::{NetworkKind, TargetResponse},
+};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use ipnet::IpNet;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+// Lista de provedores distribuída com o binário
+const BUILTIN_PROVIDERS: &str = include_str!("../data/network_providers.toml");
+
+#[derive(Debug, Deserialize)]
+struct ProviderFile {
+    #[serde(default)]
+    provider: Vec<NetworkProvider>,
+}
+
+// CDN ou hospedagem identificada pelos seus ASNs
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkProvider {
+    pub name: String,
+    pub kind: NetworkKind,
+    pub asns: Vec<u32>,
+}
+
+// Interpreta um arquivo de provedores no formato de `data/network_providers.toml`
+pub fn parse_providers(content: &str) -> Result<Vec<NetworkProvider>> {
+    let file: ProviderFile = toml::from_str(content).context("Arquivo de provedores de rede inválido")?;
+    Ok(file.provider)
+}
+
+// Origem de um endereço: ASN que anuncia o prefixo e país de registro
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub asn: u32,
+    pub network: String,
+    pub country: Option<String>,
+}
+
+// Identifica a rede dos IPs encontrados pelo serviço IP-to-ASN da Team Cymru
+//
+// As respostas são registros TXT no formato `ASN | prefixo | país | registro | data`
+// (origem) e `ASN | país | registro | data | organização` (nome do ASN).
+pub struct IpInfoLookup {
+    zone: String,
+    providers: HashMap<u32, NetworkProvider>,
+}
+
+impl IpInfoLookup {
+    pub fn from_config(config: &IpInfoConfig) -> Result<Self> {
+        let providers = match &config.providers_path {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Falha ao ler provedores de rede em {}", path))?;
+                parse_providers(&content)?
+            }
+            None => parse_providers(BUILTIN_PROVIDERS)?,
+        };
+
+        tracing::info!("ASN: {} provedores de CDN/hospedagem carregados", providers.len());
+
+        Ok(Self {
+            zone: config.zone.trim_matches('.').to_string(),
+            providers: providers
+                .into_iter()
+                .flat_map(|provider| {
+                    provider.asns.clone().into_iter().map(move |asn| (asn, provider.clone()))
+                })
+                .collect(),
+        })
+    }
+
+    // Provedor conhecido dono do ASN
+    pub fn provider(&self, asn: u32) -> Option<&NetworkProvider> {
+        self.providers.get(&asn)
+    }
+
+    // Consulta a origem de um endereço, ficando com o prefixo mais específico
+    pub async fn origin(&self, resolver: &DnsResolver, ip: IpAddr) -> Option<Origin> {
+        resolver
+            .txt(&origin_name(ip, &self.zone))
+            .await
+            .iter()
+            .filter_map(|record| parse_origin(record))
+            .max_by_key(|origin| origin.network.parse::<IpNet>().map(|net| net.prefix_len()).unwrap_or(0))
+    }
+
+    // Consulta a organização dona de um ASN
+    pub async fn as_name(&self, resolver: &DnsResolver, asn: u32) -> Option<String> {
+        resolver
+            .txt(&format!("AS{}.{}", asn, self.zone))
+            .await
+            .iter()
+            .find_map(|record| parse_as_name(record))
+    }
+
+    // Preenche ASN, organização, prefixo, país e provedor dos IPs do alvo
+    pub async fn annotate(&self, resolver: &DnsResolver, response: &mut TargetResponse) {
+        let mut ips: Vec<IpAddr> = response.ips
+            .iter()
+            .filter(|info| info.asn.is_none())
+            .filter_map(|info| info.ip.parse().ok())
+            .collect();
+        ips.sort_unstable();
+        ips.dedup();
+
+        let lookups: Vec<_> = ips
+            .into_iter()
+            .map(|ip| async move { (ip, self.origin(resolver, ip).await) })
+            .collect();
+        let origins: HashMap<IpAddr, Origin> = stream::iter(lookups)
+            .buffer_unordered(resolver.concurrency())
+            .filter_map(|(ip, origin)| async move { origin.map(|origin| (ip, origin)) })
+            .collect()
+            .await;
+
+        // Vários IPs costumam compartilhar o mesmo ASN
+        let mut asns: Vec<u32> = origins.values().map(|origin| origin.asn).collect();
+        asns.sort_unstable();
+        asns.dedup();
+
+        let lookups: Vec<_> = asns
+            .into_iter()
+            .map(|asn| async move { (asn, self.as_name(resolver, asn).await) })
+            .collect();
+        let names: HashMap<u32, String> = stream::iter(lookups)
+            .buffer_unordered(resolver.concurrency())
+            .filter_map(|(asn, name)| async move { name.map(|name| (asn, name)) })
+            .collect()
+            .await;
+
+        for info in &mut response.ips {
+            let Some(origin) = info.ip.parse().ok().and_then(|ip: IpAddr| origins.get(&ip)) else {
+                continue;
+            };
+            info.asn = Some(origin.asn);
+            info.network = Some(origin.network.clone());
+            info.country = origin.country.clone();
+            info.as_org = names.get(&origin.asn).cloned();
+            if let Some(provider) = self.provider(origin.asn) {
+                info.provider = Some(provider.name.clone());
+                info.kind = Some(provider.kind);
+            }
+        }
+
+        tracing::info!(
+            "ASN: {} de {} IPs identificados para {} ({} em bordas de CDN)",
+            origins.len(),
+            response.ips.len(),
+            response.target,
+            response.ips.iter().filter(|info| info.is_edge()).count()
+        );
+    }
+}
+
+// Nome consultado para a origem: octetos (IPv4) ou nibbles (IPv6) invertidos
+pub fn origin_name(ip: IpAddr, zone: &str) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, d] = ip.octets();
+            format!("{}.{}.{}.{}.origin.{}", d, c, b, a, zone)
+        }
+        IpAddr::V6(ip) => {
+            let nibbles: Vec<String> = ip
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|byte| [byte & 0x0f, byte >> 4])
+                .map(|nibble| format!("{:x}", nibble))
+                .collect();
+            format!("{}.origin6.{}", nibbles.join("."), zone)
+        }
+    }
+}
+
+// `13335 | 104.16.0.0/13 | US | arin | 2014-03-28`; prefixos anunciados por
+// mais de um ASN vêm como `13335 209242 | ...`, e fica o primeiro
+pub fn parse_origin(record: &str) -> Option<Origin> {
+    let fields: Vec<&str> = record.split('|').map(str::trim).collect();
+
+    let asn = fields.first()?.split_whitespace().next()?.parse().ok()?;
+    let network = fields.get(1).filter(|network| !network.is_empty())?.to_string();
+    let country = fields.get(2).filter(|country| !country.is_empty()).map(|country| country.to_string());
+
+    Some(Origin { asn, network, country })
+}
+
+// `13335 | US | arin | 2010-07-14 | CLOUDFLARENET - Cloudflare, Inc., US`
+pub fn parse_as_name(record: &str) -> Option<String> {
+    record
+        .splitn(5, '|')
+        .nth(4)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_reversed_query_names() {
+        assert_eq!(
+            origin_name("192.0.2.10".parse().unwrap(), "asn.cymru.com"),
+            "10.2.0.192.origin.asn.cymru.com"
+        );
+
+        let name = origin_name("2001:db8::7".parse().unwrap(), "asn.test");
+        assert!(name.starts_with("7.0.0.0.0.0.0.0."), "{}", name);
+        assert!(name.ends_with("8.b.d.0.1.0.0.2.origin6.asn.test"), "{}", name);
+    }
+
+    #[test]
+    fn parses_cymru_records() {
+        assert_eq!(
+            parse_origin("13335 209242 | 104.16.0.0/13 | US | arin | 2014-03-28"),
+            Some(Origin { asn: 13335, network: "104.16.0.0/13".to_string(), country: Some("US".to_string()) })
+        );
+        assert_eq!(parse_origin("NA | 192.0.2.0/24 | | |"), None);
+
+        assert_eq!(
+            parse_as_name("13335 | US | arin | 2010-07-14 | CLOUDFLARENET - Cloudflare, Inc., US").as_deref(),
+            Some("CLOUDFLARENET - Cloudflare, Inc., US")
+        );
+        assert_eq!(parse_as_name("13335 | US | arin"), None);
+    }
+
+    #[test]
+    fn builtin_providers_classify_cdn_edges() {
+        let lookup = IpInfoLookup::from_config(&IpInfoConfig::default()).unwrap();
+
+        let cloudflare = lookup.provider(13335).unwrap();
+        assert_eq!((cloudflare.name.as_str(), cloudflare.kind), ("Cloudflare", NetworkKind::Cdn));
+        assert_eq!(lookup.provider(20940).unwrap().name, "Akamai");
+        assert_eq!(lookup.provider(16509).unwrap().kind, NetworkKind::Hosting);
+        assert!(lookup.provider(64496).is_none());
+    }
+}
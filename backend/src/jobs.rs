@@ -0,0 +1,225 @@
+use crate::{
+    registry::ModuleRegistry,
+    scope::Scope,
+    types::{AppState, TargetResponse},
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+// Tempo que jobs finalizados permanecem disponíveis para consulta
+const JOB_RETENTION_MINUTES: i64 = 60;
+
+// Situação de um job de escaneamento
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+// Job de escaneamento assíncrono com resultado parcial
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanJob {
+    pub id: String,
+    pub target: String,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub result: TargetResponse,
+}
+
+impl ScanJob {
+    fn new(id: String, target: &str) -> Self {
+        Self {
+            id,
+            target: target.to_string(),
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+            result: TargetResponse::new(target),
+        }
+    }
+
+    // Registro de um escaneamento síncrono já concluído
+    pub fn from_result(result: TargetResponse) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            target: result.target.clone(),
+            status: JobStatus::Completed,
+            created_at: result.timestamp,
+            finished_at: Some(Utc::now()),
+            result,
+        }
+    }
+
+    fn finish(&mut self, status: JobStatus) {
+        self.status = status;
+        self.finished_at = Some(Utc::now());
+    }
+}
+
+struct JobEntry {
+    job: Arc<Mutex<ScanJob>>,
+    task: AbortHandle,
+}
+
+// Gerenciador dos jobs de escaneamento em memória
+#[derive(Default)]
+pub struct JobManager {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Cria um job e inicia os módulos em segundo plano
+    pub fn spawn(&self, state: Arc<AppState>, scope: Scope) -> ScanJob {
+        self.prune();
+
+        let target = &scope.target().history_key();
+
+        let id = Uuid::new_v4().to_string();
+        let job = Arc::new(Mutex::new(ScanJob::new(id.clone(), target)));
+        let snapshot = job.lock().unwrap().clone();
+
+        let task_job = job.clone();
+        let task = tokio::spawn(async move {
+            let registry = &state.registry;
+            let mut modules = registry.scan_each(&scope);
+
+            // Cada módulo finalizado é incorporado ao resultado parcial
+            while let Some((report, data)) = modules.next().await {
+                let mut job = task_job.lock().unwrap();
+                job.result.record(report, data);
+                job.result.dedup();
+            }
+
+            // A resolução DNS roda fora do lock para não bloquear consultas ao job
+            let mut result = task_job.lock().unwrap().result.clone();
+            registry.enrich(&scope, &mut result).await;
+
+            let Some(finished) = complete(&task_job, registry, result) else {
+                tracing::info!("Job cancelado durante o enriquecimento; resultado descartado");
+                return;
+            };
+
+            tracing::info!(
+                "Job {} finalizado para {}: {} subdomínios, {} IPs, {} serviços, {} URLs",
+                finished.id,
+                finished.target,
+                finished.result.subdomains.len(),
+                finished.result.ips.len(),
+                finished.result.services.len(),
+                finished.result.urls.len()
+            );
+
+            if finished.status == JobStatus::Completed {
+                state.persist(&finished).await;
+            }
+        });
+
+        self.jobs.lock().unwrap().insert(id, JobEntry {
+            job,
+            task: task.abort_handle(),
+        });
+
+        snapshot
+    }
+
+    // Estado atual de um job
+    pub fn get(&self, id: &str) -> Option<ScanJob> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(id).map(|entry| entry.job.lock().unwrap().clone())
+    }
+
+    // Cancela um job em execução, abortando os módulos pendentes
+    //
+    // O resultado parcial fica disponível aqui, mas não vai para o histórico
+    // (ver `Storage::save_scan`).
+    pub fn cancel(&self, id: &str) -> Option<ScanJob> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(id)?;
+
+        entry.task.abort();
+
+        let mut job = entry.job.lock().unwrap();
+        if job.status == JobStatus::Running {
+            job.finish(JobStatus::Cancelled);
+            tracing::info!("Job {} cancelado para {}", job.id, job.target);
+        }
+
+        Some(job.clone())
+    }
+
+    // Remove jobs finalizados há mais tempo que o período de retenção
+    fn prune(&self) {
+        let cutoff = Utc::now() - Duration::minutes(JOB_RETENTION_MINUTES);
+        self.jobs.lock().unwrap().retain(|_, entry| {
+            let job = entry.job.lock().unwrap();
+            job.finished_at.is_none_or(|finished| finished > cutoff)
+        });
+    }
+}
+
+// Conclui o job com o resultado enriquecido, se ele ainda estiver em execução
+//
+// O enriquecimento roda fora do lock, então `cancel` pode ter finalizado o job
+// nesse meio-tempo; o cancelamento prevalece e nada é persistido.
+fn complete(job: &Mutex<ScanJob>, registry: &ModuleRegistry, result: TargetResponse) -> Option<ScanJob> {
+    let mut job = job.lock().unwrap();
+    if job.status != JobStatus::Running {
+        return None;
+    }
+
+    job.result = result;
+    registry.finalize(&mut job.result);
+    job.finish(JobStatus::Completed);
+    Some(job.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enriched(target: &str) -> TargetResponse {
+        let mut result = TargetResponse::new(target);
+        result.ips.push(crate::types::IpInfo::new("192.0.2.10"));
+        result
+    }
+
+    #[test]
+    fn completes_running_jobs_with_the_enriched_result() {
+        let job = Mutex::new(ScanJob::new("1".to_string(), "corp.example"));
+
+        let finished = complete(&job, &ModuleRegistry::new(), enriched("corp.example")).unwrap();
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!(finished.result.ips.len(), 1);
+        assert!(finished.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn cancellation_during_enrichment_is_kept() {
+        let manager = JobManager::new();
+        let job = Arc::new(Mutex::new(ScanJob::new("1".to_string(), "corp.example")));
+        let task = tokio::spawn(std::future::pending::<()>()).abort_handle();
+        manager.jobs.lock().unwrap().insert("1".to_string(), JobEntry { job: job.clone(), task });
+
+        // Resultado parcial clonado para o enriquecimento, e o cancelamento chega antes do fim
+        let result = enriched("corp.example");
+        let cancelled = manager.cancel("1").unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+        assert!(complete(&job, &ModuleRegistry::new(), result).is_none());
+        let job = manager.get("1").unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert!(job.result.ips.is_empty());
+    }
+}
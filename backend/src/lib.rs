@@ -1,5 +1,20 @@
+pub mod config;
+pub mod diff;
+pub mod dns;
+pub mod events;
+pub mod http;
+pub mod ipinfo;
+pub mod jobs;
 pub mod modules;
+pub mod output;
+pub mod registry;
+pub mod scope;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+pub mod takeover;
+pub mod target;
 pub mod types;
+pub mod watch;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -8,7 +23,7 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait OsintModule: Send + Sync {
     // O método de escaneamento que todos os módulos devem implementar
-    async fn scan(&self, target: &str) -> Result<types::ModuleResult>;
+    async fn scan(&self, target: &target::Target) -> Result<types::ModuleResult>;
     
     // Nome do módulo
     fn name(&self) -> &'static str;
@@ -1,11 +1,17 @@
+#[cfg(feature = "sqlite")]
 use cortex_passivemap::{
-    OsintModule,
-    modules::{
-        crtsh::CrtShModule,
-        shodan::ShodanModule,
-        wayback::WaybackModule,
-        dorker::DorkerModule,
-    },
+    diff::{diff_scans, ScanDiff},
+    storage::{ScanSummary, Storage},
+};
+use cortex_passivemap::{
+    config::Config,
+    events::stream_scan,
+    jobs::ScanJob,
+    output::render_table,
+    registry::ModuleRegistry,
+    scope::Scope,
+    target::Target,
+    watch::{self, Watch, WatchRequest, Watchlist},
     types::{
         AppError, 
         AppState, 
@@ -14,55 +20,266 @@ use cortex_passivemap::{
     }
 };
 use axum::{
-    routing::{get, post},
+    routing::{get, post, delete},
     Router,
-    http::Method,
-    extract::{State, Json},
+    http::{Method, StatusCode},
+    extract::{State, Json, Path, Query},
+    response::sse::{Event, KeepAlive, Sse},
 };
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::{Stream, StreamExt};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tower_http::cors::{CorsLayer, Any};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+#[derive(Parser)]
+#[command(name = "cortex-passivemap", version, about = "Ferramenta de footprint digital baseada em OSINT passivo")]
+struct Cli {
+    /// Arquivo de configuração TOML (padrão: CORTEX_CONFIG ou ./cortex.toml)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inicia o servidor HTTP (padrão quando nenhum subcomando é informado)
+    Serve {
+        /// Endereço e porta de escuta (padrão: server.bind da configuração)
+        #[arg(long)]
+        bind: Option<String>,
+    },
+    /// Escaneia um ou mais alvos e imprime o resultado na saída padrão
+    Scan {
+        /// Alvo a escanear: domínio, IP, CIDR, ASN (AS15169), URL ou e-mail
+        #[arg(required_unless_present = "targets_file")]
+        target: Option<String>,
+        /// Arquivo com um alvo por linha (linhas vazias e iniciadas por # são ignoradas)
+        #[arg(long, conflicts_with = "target")]
+        targets_file: Option<PathBuf>,
+        /// Formato de saída
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+        /// Domínios adicionais no escopo (ex.: corp-cdn.net, *.corp.io)
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+        /// Nomes a descartar mesmo dentro do escopo (ex.: *.dev.corp.com)
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// JSON indentado (um array quando há vários alvos)
+    Json,
+    /// Um objeto JSON por linha
+    Jsonl,
+    /// Tabelas em texto
+    Table,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve { bind: None });
+    
+    // No modo CLI a saída padrão é reservada aos resultados
+    let default_level = match command {
+        Command::Serve { .. } => "info",
+        Command::Scan { .. } => "warn",
+    };
+    
     // Inicializa o logger
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+            std::env::var("RUST_LOG").unwrap_or_else(|_| default_level.into()),
         ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
     
     // Carrega variáveis de ambiente
     dotenv::dotenv().ok();
     
+    // Carrega a configuração (arquivo TOML + variáveis de ambiente)
+    let config = Config::load(cli.config.as_deref())?;
+    
+    match command {
+        Command::Serve { bind } => {
+            let bind = bind.unwrap_or_else(|| config.server.bind.clone());
+            serve(&config, &bind).await
+        },
+        Command::Scan { target, targets_file, format, include, exclude } => {
+            let batch = targets_file.is_some();
+            let targets = match targets_file {
+                Some(path) => read_targets_file(&path)?,
+                None => target.into_iter().collect(),
+            };
+            scan_cli(&config, targets, batch, format, &include, &exclude).await
+        },
+    }
+}
+
+// Inicializa o registro de módulos
+fn build_registry(config: &Config) -> ModuleRegistry {
+    let registry = ModuleRegistry::from_config(config);
+    tracing::info!(
+        "Módulos habilitados: {}",
+        registry.modules().iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+    );
+    registry
+}
+
+// Executa o servidor HTTP
+async fn serve(config: &Config, bind: &str) -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Iniciando CorteX PassiveMap Backend...");
     
     // Configuração CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST])
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
         .allow_headers(Any);
     
     // Inicializa o estado da aplicação
-    let app_state = Arc::new(AppState::new());
+    let watchlist = Watchlist::new(config.watch.webhook.clone());
+    let app_state = AppState::new(build_registry(config)).with_watchlist(watchlist);
+
+    // Abre o histórico de escaneamentos
+    #[cfg(feature = "sqlite")]
+    let app_state = {
+        let db_path = &config.storage.path;
+        match Storage::open(db_path) {
+            Ok(storage) => {
+                tracing::info!("Histórico de escaneamentos em {}", db_path);
+                app_state.with_storage(storage)
+            },
+            Err(e) => {
+                tracing::error!("Falha ao abrir histórico em {}: {:#}", db_path, e);
+                app_state
+            },
+        }
+    };
+    
+    // Recupera os alvos monitorados
+    #[cfg(feature = "sqlite")]
+    if let Some(storage) = &app_state.storage {
+        match storage.list_watches().await {
+            Ok(watches) => {
+                tracing::info!("{} alvos monitorados recuperados", watches.len());
+                for watch in watches {
+                    app_state.watchlist.restore(watch);
+                }
+            },
+            Err(e) => tracing::error!("Falha ao recuperar alvos monitorados: {:#}", e),
+        }
+    }
+    
+    let app_state = Arc::new(app_state);
+    
+    // Inicia o monitoramento contínuo
+    watch::spawn_scheduler(app_state.clone());
     
     // Configuração de rotas
     let app = Router::new()
         .route("/", get(health_check))
         .route("/api/target", post(scan_target))
+        .route("/api/target/stream", get(stream_target))
+        .route("/api/scans/:id", get(get_scan).delete(cancel_scan))
+        .route("/api/watch", post(create_watch).get(list_watches))
+        .route("/api/watch/:id", delete(delete_watch));
+    
+    #[cfg(feature = "sqlite")]
+    let app = app
+        .route("/api/scans", post(create_scan).get(list_scans))
+        .route("/api/targets/:target/diff", get(diff_target));
+    #[cfg(not(feature = "sqlite"))]
+    let app = app.route("/api/scans", post(create_scan));
+    
+    let app = app
         .layer(cors)
         .with_state(app_state);
     
     // Inicia o servidor
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3300").await?;
-    tracing::info!("Servidor iniciado na porta 3300");
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    tracing::info!("Servidor iniciado em {}", listener.local_addr()?);
     
     axum::serve(listener, app).await?;
     
     Ok(())
 }
 
+// Escaneia os alvos no modo CLI, um de cada vez
+//
+// Em lote (`--targets-file`), linhas inválidas são puladas com aviso antes de
+// qualquer escaneamento e a saída JSON é sempre uma lista.
+async fn scan_cli(
+    config: &Config,
+    targets: Vec<String>,
+    batch: bool,
+    format: OutputFormat,
+    include: &[String],
+    exclude: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scopes = Vec::new();
+    for raw in &targets {
+        match build_scope(raw, include, exclude, config.scan.max_network_hosts) {
+            Ok(scope) => scopes.push(scope),
+            Err(e) if batch => tracing::warn!("Alvo ignorado ({}): {}", raw, e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if scopes.is_empty() {
+        return Err("Nenhum alvo válido para escanear".into());
+    }
+    
+    let registry = build_registry(config);
+    let mut results = Vec::new();
+    
+    for scope in &scopes {
+        tracing::info!("Escaneando {}", scope.target());
+        
+        let response = registry.scan(scope).await;
+        
+        match format {
+            OutputFormat::Jsonl => println!("{}", serde_json::to_string(&response)?),
+            OutputFormat::Table => println!("{}", render_table(&response)),
+            OutputFormat::Json => results.push(response),
+        }
+    }
+    
+    if let OutputFormat::Json = format {
+        if batch {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&results[0])?);
+        }
+    }
+    
+    Ok(())
+}
+
+// Lê um alvo por linha, ignorando linhas vazias e comentários
+fn read_targets_file(path: &std::path::Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Falha ao ler {}: {}", path.display(), e))?;
+    
+    let targets: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    
+    if targets.is_empty() {
+        return Err(format!("Nenhum alvo encontrado em {}", path.display()).into());
+    }
+    
+    Ok(targets)
+}
+
 // Verificação de saúde da API
 async fn health_check() -> &'static str {
     "CorteX PassiveMap Backend: OK"
@@ -75,77 +292,247 @@ async fn scan_target(
 ) -> Result<Json<TargetResponse>, AppError> {
     tracing::info!("Recebida requisição para escanear: {}", request.target);
     
-    // Valida o alvo
-    let target = request.target.trim().to_lowercase();
-    if target.is_empty() {
-        return Err(AppError::InvalidInput("Alvo vazio fornecido".to_string()));
-    }
+    let scope = build_scope(
+        &request.target,
+        &request.include,
+        &request.exclude,
+        app_state.registry.max_network_hosts(),
+    )?;
+    let target = scope.target();
     
-    // Inicializa os módulos OSINT
-    let crtsh_module = CrtShModule::new();
-    let shodan_module = ShodanModule::new(
-        std::env::var("SHODAN_API_KEY").unwrap_or_default()
-    );
-    let wayback_module = WaybackModule::new();
-    let dorker_module = DorkerModule::new();
-    
-    // Executa os módulos em paralelo
-    let (
-        crtsh_result,
-        shodan_result,
-        wayback_result,
-        dorker_result
-    ) = tokio::join!(
-        crtsh_module.scan(&target),
-        shodan_module.scan(&target),
-        wayback_module.scan(&target),
-        dorker_module.scan(&target),
+    // Executa todos os módulos registrados em paralelo
+    let response = app_state.registry.scan(&scope).await;
+    
+    let job = ScanJob::from_result(response);
+    app_state.persist(&job).await;
+    let response = job.result;
+    
+    tracing::info!(
+        "Escaneamento completo para {}: {} subdomínios, {} IPs, {} serviços, {} URLs",
+        target,
+        response.subdomains.len(),
+        response.ips.len(),
+        response.services.len(),
+        response.urls.len()
     );
     
-    // Combina os resultados
-    let mut response = TargetResponse {
-        target: target.clone(),
-        timestamp: chrono::Utc::now(),
-        subdomains: Vec::new(),
-        ips: Vec::new(),
-        services: Vec::new(),
-        urls: Vec::new(),
-        dorks: Vec::new(),
+    Ok(Json(response))
+}
+
+// Escaneia um alvo transmitindo os resultados via Server-Sent Events
+async fn stream_target(
+    State(app_state): State<Arc<AppState>>,
+    Query(request): Query<TargetRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    tracing::info!("Recebida requisição de streaming para escanear: {}", request.target);
+    
+    let scope = build_scope(
+        &request.target,
+        &request.include,
+        &request.exclude,
+        app_state.registry.max_network_hosts(),
+    )?;
+    
+    let events = stream_scan(app_state, scope)
+        .map(|event| Event::default().event(event.kind()).json_data(&event));
+    
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// Inicia um escaneamento assíncrono e retorna o ID do job
+async fn create_scan(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<TargetRequest>,
+) -> Result<(StatusCode, Json<ScanJob>), AppError> {
+    tracing::info!("Recebida requisição de job para escanear: {}", request.target);
+    
+    let scope = build_scope(
+        &request.target,
+        &request.include,
+        &request.exclude,
+        app_state.registry.max_network_hosts(),
+    )?;
+    
+    let job = app_state.jobs.spawn(app_state.clone(), scope);
+    tracing::info!("Job {} criado para {}", job.id, job.target);
+    
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+// Filtro da listagem de escaneamentos armazenados
+#[cfg(feature = "sqlite")]
+#[derive(Debug, serde::Deserialize)]
+struct ScanListQuery {
+    target: Option<String>,
+}
+
+// Lista os escaneamentos armazenados, opcionalmente filtrados por alvo
+#[cfg(feature = "sqlite")]
+async fn list_scans(
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<ScanListQuery>,
+) -> Result<Json<Vec<ScanSummary>>, AppError> {
+    let storage = app_state.storage
+        .as_ref()
+        .ok_or_else(|| AppError::InternalError("Histórico de escaneamentos indisponível".to_string()))?;
+    
+    let target = query.target
+        .map(|target| normalize_target(&target))
+        .transpose()?;
+    
+    Ok(Json(storage.list_scans(target).await?))
+}
+
+// Escaneamentos a comparar (por padrão, os dois mais recentes)
+#[cfg(feature = "sqlite")]
+#[derive(Debug, serde::Deserialize)]
+struct DiffQuery {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+// Compara dois escaneamentos armazenados de um alvo
+#[cfg(feature = "sqlite")]
+async fn diff_target(
+    State(app_state): State<Arc<AppState>>,
+    Path(target): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<ScanDiff>, AppError> {
+    let storage = app_state.storage
+        .as_ref()
+        .ok_or_else(|| AppError::InternalError("Histórico de escaneamentos indisponível".to_string()))?;
+    
+    let target = normalize_target(&target)?;
+    
+    // Histórico do alvo, do mais recente ao mais antigo
+    let history = storage.list_scans(Some(target.clone())).await?;
+    
+    let position = |id: &str| {
+        history
+            .iter()
+            .position(|scan| scan.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Escaneamento {} não encontrado para {}", id, target)))
     };
     
-    // Adiciona resultados de cada módulo
-    if let Ok(crtsh_data) = crtsh_result {
-        response.subdomains.extend(crtsh_data.subdomains);
-    }
+    let to_index = match &query.to {
+        Some(id) => position(id)?,
+        None => 0,
+    };
+    let from_index = match &query.from {
+        Some(id) => position(id)?,
+        None => to_index + 1,
+    };
     
-    if let Ok(shodan_data) = shodan_result {
-        response.ips.extend(shodan_data.ips);
-        response.services.extend(shodan_data.services);
-    }
+    let (Some(from), Some(to)) = (history.get(from_index), history.get(to_index)) else {
+        return Err(AppError::NotFound(format!(
+            "Histórico insuficiente para comparar escaneamentos de {}",
+            target
+        )));
+    };
+    
+    let from = storage.get_scan(&from.id).await?;
+    let to = storage.get_scan(&to.id).await?;
     
-    if let Ok(wayback_data) = wayback_result {
-        response.urls.extend(wayback_data.urls);
+    match (from, to) {
+        (Some(from), Some(to)) => Ok(Json(diff_scans(&from, &to))),
+        _ => Err(AppError::NotFound(format!("Escaneamentos de {} não encontrados", target))),
+    }
+}
+
+// Consulta um job em andamento ou um escaneamento armazenado
+async fn get_scan(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ScanJob>, AppError> {
+    if let Some(job) = app_state.jobs.get(&id) {
+        return Ok(Json(job));
     }
     
-    if let Ok(dorker_data) = dorker_result {
-        response.dorks.extend(dorker_data.dorks);
+    #[cfg(feature = "sqlite")]
+    if let Some(storage) = &app_state.storage {
+        if let Some(job) = storage.get_scan(&id).await? {
+            return Ok(Json(job));
+        }
     }
     
-    // Remove duplicatas
-    response.subdomains.sort_unstable();
-    response.subdomains.dedup();
+    Err(AppError::NotFound(format!("Escaneamento {} não encontrado", id)))
+}
+
+// Cancela um job em execução
+async fn cancel_scan(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ScanJob>, AppError> {
+    app_state.jobs
+        .cancel(&id)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Job {} não encontrado", id)))
+}
+
+// Registra um alvo para monitoramento contínuo
+async fn create_watch(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<WatchRequest>,
+) -> Result<(StatusCode, Json<Watch>), AppError> {
+    let scope = build_scope(
+        &request.target,
+        &request.include,
+        &request.exclude,
+        app_state.registry.max_network_hosts(),
+    )?;
     
-    response.ips.sort_unstable();
-    response.ips.dedup();
+    let watch = app_state.watchlist.add(&scope.target().history_key(), &request)?;
+    watch::save_watch(&app_state, &watch).await;
     
-    tracing::info!(
-        "Escaneamento completo para {}: {} subdomínios, {} IPs, {} serviços, {} URLs",
-        target,
-        response.subdomains.len(),
-        response.ips.len(),
-        response.services.len(),
-        response.urls.len()
-    );
+    tracing::info!("Monitorando {} a cada {} ({})", watch.target, watch.interval, watch.id);
     
-    Ok(Json(response))
-} 
\ No newline at end of file
+    Ok((StatusCode::CREATED, Json(watch)))
+}
+
+// Lista os alvos monitorados
+async fn list_watches(
+    State(app_state): State<Arc<AppState>>,
+) -> Json<Vec<Watch>> {
+    Json(app_state.watchlist.list())
+}
+
+// Remove um alvo do monitoramento
+async fn delete_watch(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Watch>, AppError> {
+    let watch = app_state.watchlist
+        .remove(&id)
+        .ok_or_else(|| AppError::NotFound(format!("Monitoramento {} não encontrado", id)))?;
+    
+    #[cfg(feature = "sqlite")]
+    if let Some(storage) = &app_state.storage {
+        storage.delete_watch(&id).await?;
+    }
+    
+    tracing::info!("Monitoramento de {} removido ({})", watch.target, watch.id);
+    
+    Ok(Json(watch))
+}
+
+// Valida e normaliza o alvo recebido, na forma usada pelo histórico
+#[cfg(feature = "sqlite")]
+fn normalize_target(raw: &str) -> Result<String, AppError> {
+    raw.parse::<Target>()
+        .map(|target| target.history_key())
+        .map_err(AppError::InvalidInput)
+}
+
+// Monta o escopo do escaneamento a partir do alvo e dos padrões da requisição
+fn build_scope(
+    raw: &str,
+    include: &[String],
+    exclude: &[String],
+    max_network_hosts: usize,
+) -> Result<Scope, AppError> {
+    let target: Target = raw.parse().map_err(AppError::InvalidInput)?;
+    target.check_size(max_network_hosts).map_err(AppError::InvalidInput)?;
+    Scope::new(target)
+        .and_then(|scope| scope.with_patterns(include, exclude))
+        .map_err(|e| AppError::InvalidInput(e.to_string()))
+}
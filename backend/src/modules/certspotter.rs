@@ -0,0 +1,243 @@
+use crate::{OsintModule, config::CertSpotterConfig, http::HttpClient, scope::{is_within, normalize_name}, target::Target, types::{Certificate, Subdomain, ModuleResult}};
+use anyhow::{Result, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Emissão de certificado retornada pela API de issuances
+#[derive(Debug, Deserialize)]
+struct Issuance {
+    id: String,
+    #[serde(default)]
+    dns_names: Vec<String>,
+    issuer: Option<Issuer>,
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issuer {
+    // Nome distinto do emissor, no mesmo formato usado pelo crt.sh
+    name: Option<String>,
+    friendly_name: Option<String>,
+}
+
+pub struct CertSpotterModule {
+    config: CertSpotterConfig,
+    http: HttpClient,
+}
+
+impl Default for CertSpotterModule {
+    fn default() -> Self {
+        Self::new(CertSpotterConfig::default(), HttpClient::default())
+    }
+}
+
+impl CertSpotterModule {
+    pub fn new(config: CertSpotterConfig, http: HttpClient) -> Self {
+        Self { config, http }
+    }
+
+    // Consulta uma página de emissões, a partir do último id recebido
+    async fn fetch_page(&self, target: &str, after: Option<&str>) -> Result<Vec<Issuance>> {
+        let mut url = format!(
+            "{}/v1/issuances?domain={}&include_subdomains=true&expand=dns_names&expand=issuer",
+            self.config.base_url.trim_end_matches('/'),
+            urlencoding::encode(target)
+        );
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", urlencoding::encode(after)));
+        }
+
+        let authorization = format!("Bearer {}", self.config.api_key);
+        let headers: Vec<(&str, &str)> = if self.config.api_key.is_empty() {
+            Vec::new()
+        } else {
+            vec![("Authorization", authorization.as_str())]
+        };
+
+        let response = self.http
+            .get_with_headers(&url, &headers, Duration::from_secs(self.config.timeout_secs))
+            .await
+            .context("Falha ao consultar certspotter")?
+            .error_for_status()?;
+
+        response.json().context("Falha ao parsear resposta do certspotter")
+    }
+
+    // Percorre as páginas de emissões até o limite configurado
+    //
+    // Uma página seguinte que falha vira aviso, mantendo as anteriores.
+    async fn fetch_issuances(&self, target: &str) -> Result<(Vec<Issuance>, Vec<String>)> {
+        let mut issuances: Vec<Issuance> = Vec::new();
+        let mut warnings = Vec::new();
+
+        for page in 0..self.config.max_pages.max(1) {
+            let after = issuances.last().map(|issuance| issuance.id.clone());
+            let batch = match self.fetch_page(target, after.as_deref()).await {
+                Ok(batch) => batch,
+                // Falhas após a primeira página mantêm o que já foi obtido
+                Err(e) if page > 0 => {
+                    tracing::warn!("certspotter: paginação interrompida para {}: {:#}", target, e);
+                    warnings.push(format!("Página {}: {:#}", page + 1, e));
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if batch.is_empty() {
+                break;
+            }
+            issuances.extend(batch);
+        }
+
+        Ok((issuances, warnings))
+    }
+}
+
+#[async_trait]
+impl OsintModule for CertSpotterModule {
+    fn name(&self) -> &'static str {
+        "certspotter"
+    }
+
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
+        let target = target.require_domain(self.name())?;
+        let mut result = ModuleResult::default();
+
+        tracing::info!("Consultando certspotter para {}", target);
+
+        let (issuances, warnings) = self.fetch_issuances(target).await?;
+        result.warnings = warnings;
+
+        // Agrega as emissões por nome; cada emissão vira um certificado
+        let mut subdomains: BTreeMap<String, Subdomain> = BTreeMap::new();
+        let mut certificates: BTreeMap<String, Certificate> = BTreeMap::new();
+        for issuance in &issuances {
+            let issuer = issuance.issuer
+                .as_ref()
+                .and_then(|issuer| issuer.name.clone().or_else(|| issuer.friendly_name.clone()));
+
+            // Ids prefixados: a numeração do certspotter não é a do crt.sh
+            let certificate_id = format!("{}:{}", self.name(), issuance.id);
+            let mut in_scope = false;
+
+            let names: Vec<(String, bool)> = issuance.dns_names
+                .iter()
+                .filter_map(|dns_name| normalize_name(dns_name))
+                .collect();
+
+            for (name, wildcard) in &names {
+                if !is_within(name, target) {
+                    continue;
+                }
+                in_scope = true;
+
+                let subdomain = Subdomain {
+                    name: name.clone(),
+                    ip: None,
+                    cnames: Vec::new(),
+                    wildcard: *wildcard,
+                    first_seen: issuance.not_before,
+                    last_seen: issuance.not_after,
+                    issuer: issuer.clone(),
+                    certificates: vec![certificate_id.clone()],
+                    records: Vec::new(),
+                    source: self.name().to_string(),
+                    sources: Vec::new(),
+                };
+
+                match subdomains.get_mut(name) {
+                    Some(existing) => existing.absorb(subdomain),
+                    None => {
+                        subdomains.insert(name.clone(), subdomain);
+                    }
+                }
+            }
+
+            // Emissões sem nomes do alvo são descartadas, como no crt.sh
+            if in_scope {
+                certificates.entry(certificate_id.clone()).or_insert_with(|| Certificate {
+                    id: certificate_id,
+                    serial_number: None,
+                    issuer,
+                    common_name: None,
+                    names: names
+                        .iter()
+                        .map(|(name, wildcard)| if *wildcard { format!("*.{}", name) } else { name.clone() })
+                        .collect(),
+                    not_before: issuance.not_before,
+                    not_after: issuance.not_after,
+                    logged_at: None,
+                    source: self.name().to_string(),
+                });
+            }
+        }
+
+        result.subdomains = subdomains.into_values().collect();
+        result.certificates = certificates.into_values().collect();
+
+        tracing::info!(
+            "certspotter: {} emissões, {} subdomínios e {} certificados para {}",
+            issuances.len(),
+            result.subdomains.len(),
+            result.certificates.len(),
+            target
+        );
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scan_follows_pages_and_aggregates_names() {
+        let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/certspotter"));
+        let result = CertSpotterModule::new(CertSpotterConfig::default(), http)
+            .scan(&"corp.example".parse().unwrap())
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = result.subdomains.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["api.corp.example", "corp.example", "www.corp.example"]);
+
+        // www aparece em duas emissões: primeira e última datas são combinadas
+        let www = &result.subdomains[2];
+        assert_eq!(www.first_seen.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(www.last_seen.unwrap().to_rfc3339(), "2024-05-30T00:00:00+00:00");
+        assert_eq!(www.issuer.as_deref(), Some("C=US, O=Let's Encrypt, CN=R3"));
+
+        let api = &result.subdomains[0];
+        assert_eq!(api.issuer.as_deref(), Some("C=US, O=DigiCert Inc, CN=DigiCert TLS RSA SHA256 2020 CA1"));
+        assert_eq!(api.source, "certspotter");
+        assert!(api.sources.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scan_exposes_issuances_as_certificates() {
+        let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/certspotter"));
+        let result = CertSpotterModule::new(CertSpotterConfig::default(), http)
+            .scan(&"corp.example".parse().unwrap())
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = result.certificates.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["certspotter:5000000001", "certspotter:5000000002", "certspotter:5000000003"]);
+
+        // Nomes fora do alvo continuam no certificado, mas não viram subdomínios;
+        // os nomes passam pela mesma normalização do crt.sh (caixa, ponto final, e-mails)
+        let last = &result.certificates[2];
+        assert_eq!(last.names, vec!["www.corp.example", "shop.other.test"]);
+        assert_eq!(last.issuer.as_deref(), Some("C=US, O=Let's Encrypt, CN=R3"));
+        assert_eq!(last.not_after.unwrap().to_rfc3339(), "2024-05-30T00:00:00+00:00");
+        assert_eq!(last.source, "certspotter");
+
+        let www = result.subdomains.iter().find(|s| s.name == "www.corp.example").unwrap();
+        assert_eq!(www.certificates, vec!["certspotter:5000000001", "certspotter:5000000003"]);
+    }
+}
@@ -1,84 +1,108 @@
-use crate::{OsintModule, types::{Subdomain, ModuleResult}};
+use crate::{OsintModule, config::CrtShConfig, http::HttpClient, scope::{is_within, normalize_name}, target::Target, types::{Certificate, Subdomain, ModuleResult}};
 use anyhow::{Result, Context};
 use async_trait::async_trait;
 use serde::Deserialize;
 use chrono::{DateTime, Utc, NaiveDateTime, TimeZone};
-use regex::Regex;
-use std::collections::HashSet;
-use futures::future::join_all;
+use std::collections::BTreeMap;
+use futures::stream::{self, StreamExt};
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 struct CrtShEntry {
+    id: Option<u64>,
     name_value: String,
+    common_name: Option<String>,
+    issuer_name: Option<String>,
+    serial_number: Option<String>,
+    entry_timestamp: Option<String>,
     #[serde(rename = "not_before")]
     first_seen: Option<String>,
     #[serde(rename = "not_after")]
     last_seen: Option<String>,
 }
 
-#[derive(Default)]
-pub struct CrtShModule;
+pub struct CrtShModule {
+    config: CrtShConfig,
+    http: HttpClient,
+}
+
+impl Default for CrtShModule {
+    fn default() -> Self {
+        Self::new(CrtShConfig::default(), HttpClient::default())
+    }
+}
 
 impl CrtShModule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: CrtShConfig, http: HttpClient) -> Self {
+        Self { config, http }
     }
     
     // Função auxiliar para converter timestamp para DateTime
     fn parse_timestamp(&self, timestamp: &str) -> Option<DateTime<Utc>> {
-        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S")
+        // entry_timestamp inclui frações de segundo; not_before/not_after não
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f")
             .ok()
             .map(|ndt| Utc.from_utc_datetime(&ndt))
     }
     
     // Consulta paralelizada para diferentes variações da pesquisa
-    async fn fetch_crt_data(&self, target: &str) -> Result<Vec<CrtShEntry>> {
+    //
+    // Devolve as entradas e a descrição das consultas que falharam.
+    async fn fetch_crt_data(&self, target: &str) -> Result<(Vec<CrtShEntry>, Vec<String>)> {
         // Diferentes estratégias de consulta para maximizar resultados
-        let query_strategies = vec![
+        let query_strategies = [
             format!("%.{}", target),           // Subdomínios diretos
             format!("%.%.{}", target),         // Subdomínios de segundo nível
             format!("%25.{}", target),         // URL encoded
         ];
         
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .context("Falha ao criar cliente HTTP")?;
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let timeout = Duration::from_secs(self.config.timeout_secs);
         
         // Executa consultas em paralelo
-        let fetch_futures = query_strategies.iter().map(|query| {
-            let client = &client;
-            let url = format!("https://crt.sh/?q={}&output=json", query);
+        let fetch_futures: Vec<_> = query_strategies.iter().map(|query| {
+            let http = &self.http;
+            let url = format!("{}/?q={}&output=json", base_url, query);
             
             async move {
-                match client.get(&url).send().await {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            match response.json::<Vec<CrtShEntry>>().await {
-                                Ok(entries) => Ok::<Vec<CrtShEntry>, anyhow::Error>(entries),
-                                Err(_) => Ok(vec![])
-                            }
-                        } else {
-                            Ok(vec![])
-                        }
-                    },
-                    Err(_) => Ok(vec![])
-                }
+                let response = http.get(&url, timeout)
+                    .await
+                    .context("Falha ao consultar crt.sh")?
+                    .error_for_status()?;
+                
+                response.json::<Vec<CrtShEntry>>()
+                    .context("Falha ao parsear resposta do crt.sh")
             }
-        });
+        }).collect();
         
         // Combina os resultados
-        let results = join_all(fetch_futures).await;
+        let results: Vec<_> = stream::iter(fetch_futures)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await;
         
-        // Flatten e filtra erros
+        // Mantém as consultas bem-sucedidas; só falha se todas falharem
         let mut all_entries = Vec::new();
+        let mut warnings = Vec::new();
+        let mut last_error = None;
         for result in results {
-            if let Ok(entries) = result {
-                all_entries.extend(entries);
+            match result {
+                Ok(entries) => all_entries.extend(entries),
+                Err(e) => {
+                    tracing::warn!("crt.sh: consulta falhou para {}: {:#}", target, e);
+                    warnings.push(format!("{:#}", e));
+                    last_error = Some(e);
+                }
+            }
+        }
+        
+        if let Some(e) = last_error {
+            if all_entries.is_empty() {
+                return Err(e);
             }
         }
         
-        Ok(all_entries)
+        Ok((all_entries, warnings))
     }
 }
 
@@ -88,40 +112,38 @@ impl OsintModule for CrtShModule {
         "crt.sh"
     }
     
-    async fn scan(&self, target: &str) -> Result<ModuleResult> {
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
+        let target = target.require_domain(self.name())?;
         let mut result = ModuleResult::default();
         
         tracing::info!("Consultando crt.sh para {}", target);
         
         // Obtém dados do crt.sh com consultas paralelas
-        let entries = self.fetch_crt_data(target).await?;
+        let (entries, warnings) = self.fetch_crt_data(target).await?;
+        result.warnings = warnings;
         
-        // Expressão regular para limpar wildcards e caracteres inválidos
-        let wildcard_regex = Regex::new(r"^\*\.").unwrap();
-        
-        // Conjunto para evitar duplicatas durante o processamento
-        let mut unique_subdomains = HashSet::new();
+        // Subdomínios agregados por nome, somando todos os certificados que os citam
+        let mut subdomains: BTreeMap<String, Subdomain> = BTreeMap::new();
+        // Certificados por id numérico; as consultas paralelas repetem entradas
+        let mut certificates: BTreeMap<u64, Certificate> = BTreeMap::new();
         
         // Processa os resultados
         for entry in entries {
-            let subdomain_name = wildcard_regex
-                .replace(&entry.name_value, "")
-                .to_string()
-                .trim()
-                .to_lowercase();
+            // name_value traz um SAN por linha
+            let names: Vec<(String, bool)> = entry.name_value
+                .lines()
+                .filter_map(normalize_name)
+                .collect();
             
-            // Verifica se é um subdomínio válido do alvo
-            if !subdomain_name.ends_with(target) && subdomain_name != target {
+            // Verifica se algum nome pertence ao alvo
+            let in_scope: Vec<&(String, bool)> = names
+                .iter()
+                .filter(|(name, _)| is_within(name, target))
+                .collect();
+            if in_scope.is_empty() {
                 continue;
             }
             
-            // Evita duplicatas durante o processamento
-            if unique_subdomains.contains(&subdomain_name) {
-                continue;
-            }
-            
-            unique_subdomains.insert(subdomain_name.clone());
-            
             // Converte timestamps
             let first_seen = entry.first_seen
                 .as_deref()
@@ -131,19 +153,109 @@ impl OsintModule for CrtShModule {
                 .as_deref()
                 .and_then(|ts| self.parse_timestamp(ts));
             
-            // Adiciona o subdomínio à lista de resultados
-            result.subdomains.push(Subdomain {
-                name: subdomain_name,
-                ip: None, // O crt.sh não fornece IPs
-                first_seen,
-                last_seen,
-                source: self.name().to_string(),
-            });
+            let certificate_id = entry.id.map(|id| id.to_string());
+            if let (Some(id), Some(key)) = (&certificate_id, entry.id) {
+                certificates.entry(key).or_insert_with(|| Certificate {
+                    id: id.clone(),
+                    serial_number: entry.serial_number.clone(),
+                    issuer: entry.issuer_name.clone(),
+                    common_name: entry.common_name.clone(),
+                    names: names
+                        .iter()
+                        .map(|(name, wildcard)| if *wildcard { format!("*.{}", name) } else { name.clone() })
+                        .collect(),
+                    not_before: first_seen,
+                    not_after: last_seen,
+                    logged_at: entry.entry_timestamp.as_deref().and_then(|ts| self.parse_timestamp(ts)),
+                    source: self.name().to_string(),
+                });
+            }
+            
+            for (name, wildcard) in in_scope {
+                let subdomain = Subdomain {
+                    name: name.clone(),
+                    ip: None, // O crt.sh não fornece IPs
+                    cnames: Vec::new(),
+                    wildcard: *wildcard,
+                    first_seen,
+                    last_seen,
+                    issuer: entry.issuer_name.clone(),
+                    certificates: certificate_id.iter().cloned().collect(),
+                    records: Vec::new(),
+                    source: self.name().to_string(),
+                    sources: Vec::new(),
+                };
+                
+                // Mantém a primeira e a última data entre todos os certificados
+                match subdomains.get_mut(name) {
+                    Some(existing) => existing.absorb(subdomain),
+                    None => {
+                        subdomains.insert(name.clone(), subdomain);
+                    }
+                }
+            }
         }
         
-        // Já não precisamos fazer sort+dedup aqui pois já usamos um HashSet
-        tracing::info!("crt.sh: encontrados {} subdomínios para {}", result.subdomains.len(), target);
+        result.subdomains = subdomains.into_values().collect();
+        result.certificates = certificates.into_values().collect();
+        
+        tracing::info!(
+            "crt.sh: encontrados {} subdomínios e {} certificados para {}",
+            result.subdomains.len(),
+            result.certificates.len(),
+            target
+        );
         
         Ok(result)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module() -> CrtShModule {
+        let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/crtsh"));
+        CrtShModule::new(CrtShConfig::default(), http)
+    }
+
+    #[tokio::test]
+    async fn scan_uses_recorded_responses() {
+        let result = module().scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+        let mut names: Vec<&str> = result.subdomains.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["api.corp.example", "mail.corp.example", "www.corp.example"]);
+
+        let mail = result.subdomains.iter().find(|s| s.name == "mail.corp.example").unwrap();
+        assert_eq!(mail.first_seen.unwrap().to_rfc3339(), "2023-06-01T00:00:00+00:00");
+        assert_eq!(mail.last_seen.unwrap().to_rfc3339(), "2023-08-30T00:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn scan_keeps_certificate_metadata() {
+        let result = module().scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+        // www aparece nas duas consultas, mas o certificado é o mesmo;
+        // certificados sem nomes do alvo são descartados
+        let ids: Vec<&str> = result.certificates.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["9876543210", "9876543211", "9876543213"]);
+
+        let www = &result.certificates[0];
+        assert_eq!(www.serial_number.as_deref(), Some("04a1b2c3d4"));
+        assert_eq!(www.issuer.as_deref(), Some("C=US, O=Let's Encrypt, CN=R3"));
+        assert_eq!(www.common_name.as_deref(), Some("www.corp.example"));
+        assert_eq!(www.names, vec!["www.corp.example"]);
+        assert_eq!(www.not_after.unwrap().to_rfc3339(), "2024-03-31T00:00:00+00:00");
+        assert_eq!(www.logged_at.unwrap().to_rfc3339(), "2024-01-01T01:02:03.456+00:00");
+        assert_eq!(www.source, "crt.sh");
+
+        let subdomain = result.subdomains.iter().find(|s| s.name == "www.corp.example").unwrap();
+        assert_eq!(subdomain.certificates, vec!["9876543210"]);
+    }
+
+    #[tokio::test]
+    async fn scan_fails_without_any_recorded_response() {
+        assert!(module().scan(&"other.example".parse().unwrap()).await.is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{OsintModule, types::{Dork, ModuleResult}};
+use crate::{OsintModule, target::Target, types::{Dork, ModuleResult}};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashSet;
@@ -41,6 +41,7 @@ const TECH_DORKS: &[(&str, &str)] = &[
     ("site:{target} inurl:jira", "Jira"),
 ];
 
+#[derive(Default)]
 pub struct DorkerModule;
 
 impl DorkerModule {
@@ -125,7 +126,8 @@ impl OsintModule for DorkerModule {
         "Dork Generator"
     }
     
-    async fn scan(&self, target: &str) -> Result<ModuleResult> {
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
+        let target = target.require_domain(self.name())?;
         let mut result = ModuleResult::default();
         
         tracing::info!("Gerando dorks avançados para {}", target);
@@ -1,4 +1,5 @@
 pub mod crtsh;
+pub mod certspotter;
 pub mod shodan;
 pub mod wayback;
 pub mod dorker; 
\ No newline at end of file
@@ -1,13 +1,23 @@
-use crate::{OsintModule, types::{Service, ModuleResult, Subdomain}};
-use anyhow::Result;
+use crate::{OsintModule, config::{ShodanConfig, ShodanMode}, http::HttpClient, scope::is_within, target::Target, types::{CreditUsage, DnsRecord, FacetCount, HttpInfo, Service, ModuleResult, ModuleSkipped, Subdomain, TlsInfo, Vulnerability}};
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, de::DeserializeOwned};
+use std::collections::{BTreeMap, BTreeSet, HashSet, HashMap};
 use std::net::IpAddr;
-use std::collections::{HashSet, HashMap};
-use futures::future::join_all;
-use tokio::time::timeout;
+use std::sync::atomic::{AtomicU32, Ordering};
+use futures::stream::{self, StreamExt};
 use std::time::Duration;
 
+// Resultados por página da busca do Shodan
+const PAGE_SIZE: usize = 100;
+
+// Nomes ou IPs por requisição em `/dns/resolve` e `/dns/reverse`
+const DNS_BATCH_SIZE: usize = 100;
+
+// Fonte dos subdomínios vindos dos endpoints de DNS
+const DNS_SOURCE: &str = "Shodan DNS";
+
 // Estruturas para deserialização da API do Shodan
 #[derive(Debug, Deserialize)]
 struct ShodanResponse {
@@ -16,152 +26,684 @@ struct ShodanResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct ShodanMatch {
     ip_str: String,
-    port: u16,
-    transport: Option<String>,
-    product: Option<String>,
-    version: Option<String>,
-    data: Option<String>,
     hostnames: Option<Vec<String>>,
     domains: Option<Vec<String>>,
+    #[serde(flatten)]
+    banner: ShodanBanner,
+}
+
+// Saldo da conta (`/api-info`)
+#[derive(Debug, Deserialize)]
+struct ShodanApiInfo {
+    query_credits: Option<u32>,
+    plan: Option<String>,
+}
+
+// Contagem por facetas (`/shodan/host/count`)
+#[derive(Debug, Deserialize)]
+struct ShodanCountResponse {
+    total: Option<u64>,
+    #[serde(default)]
+    facets: HashMap<String, Vec<ShodanFacetBucket>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanFacetBucket {
+    count: u64,
+    value: serde_json::Value,
+}
+
+// Subdomínios e registros de um domínio (`/dns/domain/{domain}`)
+#[derive(Debug, Deserialize)]
+struct ShodanDomainResponse {
+    #[serde(default)]
+    data: Vec<ShodanDnsEntry>,
+    // Há mais páginas de registros
+    #[serde(default)]
+    more: bool,
 }
 
 #[derive(Debug, Deserialize)]
+struct ShodanDnsEntry {
+    // Rótulo relativo ao domínio; vazio para o próprio domínio
+    #[serde(default)]
+    subdomain: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    value: String,
+    last_seen: Option<String>,
+}
+
+// Créditos de consulta que um escaneamento ainda pode gastar
+//
+// Compartilhado pelas buscas em paralelo; cada página de busca custa um crédito.
+struct CreditBudget {
+    limit: Option<u32>,
+    remaining: Option<u32>,
+    budget: Option<u32>,
+    used: AtomicU32,
+    skipped: AtomicU32,
+}
+
+impl CreditBudget {
+    fn new(budget: u32, remaining: Option<u32>) -> Self {
+        let budget = (budget > 0).then_some(budget);
+        let limit = match (budget, remaining) {
+            (Some(budget), Some(remaining)) => Some(budget.min(remaining)),
+            (budget, remaining) => budget.or(remaining),
+        };
+
+        Self { limit, remaining, budget, used: AtomicU32::new(0), skipped: AtomicU32::new(0) }
+    }
+
+    fn available(&self) -> bool {
+        self.limit.is_none_or(|limit| self.used.load(Ordering::SeqCst) < limit)
+    }
+
+    // Reserva um crédito; recusa (e contabiliza) quando o orçamento acabou
+    fn spend(&self) -> bool {
+        let spent = self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| match self.limit {
+                Some(limit) if used >= limit => None,
+                _ => Some(used + 1),
+            })
+            .is_ok();
+        if !spent {
+            self.skipped.fetch_add(1, Ordering::SeqCst);
+        }
+        spent
+    }
+
+    fn usage(&self) -> CreditUsage {
+        CreditUsage {
+            used: self.used.load(Ordering::SeqCst),
+            remaining: self.remaining,
+            budget: self.budget,
+            skipped: self.skipped.load(Ordering::SeqCst),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct ShodanHostResponse {
     ip_str: String,
     ports: Option<Vec<u16>>,
     hostnames: Option<Vec<String>>,
     domains: Option<Vec<String>>,
-    data: Option<Vec<ShodanDataDetail>>,
+    data: Option<Vec<ShodanBanner>>,
 }
 
+// Banner de um serviço, comum à busca e aos detalhes de host
 #[derive(Debug, Deserialize)]
-struct ShodanDataDetail {
+struct ShodanBanner {
     port: u16,
     transport: Option<String>,
     product: Option<String>,
     version: Option<String>,
     data: Option<String>,
+    http: Option<ShodanHttp>,
+    ssl: Option<ShodanSsl>,
+    #[serde(default)]
+    cpe23: Vec<String>,
+    #[serde(default)]
+    cpe: Vec<String>,
+    vulns: Option<ShodanVulns>,
+    timestamp: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanHttp {
+    status: Option<u16>,
+    title: Option<String>,
+    server: Option<String>,
+    #[serde(default)]
+    redirects: Vec<ShodanRedirect>,
+    favicon: Option<ShodanFavicon>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanRedirect {
+    location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanFavicon {
+    hash: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanSsl {
+    cert: Option<ShodanCert>,
+    // Versões recusadas vêm com `-` na frente (ex.: `-SSLv3`)
+    #[serde(default)]
+    versions: Vec<String>,
+    cipher: Option<ShodanCipher>,
+    jarm: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanCert {
+    #[serde(default)]
+    subject: HashMap<String, String>,
+    #[serde(default)]
+    issuer: HashMap<String, String>,
+    // Formato ASN.1 (`20240331000000Z`)
+    expires: Option<String>,
+    #[serde(default)]
+    fingerprint: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanCipher {
+    name: Option<String>,
+}
+
+// Os banners trazem as CVEs com detalhes; algumas respostas, só os ids
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ShodanVulns {
+    Detailed(HashMap<String, ShodanVuln>),
+    Ids(Vec<String>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ShodanVuln {
+    // Número na maioria das respostas, texto em algumas
+    cvss: Option<serde_json::Value>,
+    #[serde(default)]
+    verified: bool,
+    summary: Option<String>,
+}
+
+impl ShodanBanner {
+    // Converte o banner em serviço, com os detalhes estruturados que vierem
+    fn into_service(self, ip: String, source: &str) -> Service {
+        let service = match (self.product, self.version) {
+            (Some(prod), Some(ver)) => format!("{} {}", prod, ver),
+            (Some(prod), None) => prod,
+            (None, _) => self.transport.clone().unwrap_or_else(|| "unknown".to_string()),
+        };
+
+        let vulns = match self.vulns {
+            Some(ShodanVulns::Detailed(vulns)) => vulns.into_iter().collect(),
+            Some(ShodanVulns::Ids(ids)) => ids.into_iter().map(|id| (id, ShodanVuln::default())).collect(),
+            None => Vec::new(),
+        };
+        let mut cves: Vec<Vulnerability> = vulns
+            .into_iter()
+            .map(|(id, vuln)| Vulnerability {
+                id,
+                // "NaN" e "inf" também são aceitos por `parse`; notas não finitas são descartadas
+                cvss: vuln.cvss
+                    .and_then(|cvss| match cvss {
+                        serde_json::Value::String(cvss) => cvss.trim().parse::<f64>().ok(),
+                        cvss => cvss.as_f64(),
+                    })
+                    .filter(|cvss| cvss.is_finite()),
+                verified: vuln.verified,
+                summary: vuln.summary,
+            })
+            .collect();
+        cves.sort_by(|a, b| {
+            b.cvss.unwrap_or(-1.0).total_cmp(&a.cvss.unwrap_or(-1.0)).then_with(|| a.id.cmp(&b.id))
+        });
+
+        Service {
+            ip,
+            port: self.port,
+            transport: self.transport.unwrap_or_else(|| "tcp".to_string()),
+            service,
+            banner: self.data,
+            source: source.to_string(),
+            http: self.http.map(|http| HttpInfo {
+                status: http.status,
+                title: http.title,
+                server: http.server,
+                favicon_hash: http.favicon.and_then(|favicon| favicon.hash),
+                redirects: http.redirects.into_iter().filter_map(|redirect| redirect.location).collect(),
+            }),
+            tls: self.ssl.map(|ssl| {
+                let cert = ssl.cert;
+                TlsInfo {
+                    subject: cert.as_ref().and_then(|cert| cert.subject.get("CN").cloned()),
+                    issuer: cert.as_ref().and_then(|cert| distinguished_name(&cert.issuer)),
+                    not_after: cert.as_ref().and_then(|cert| {
+                        let expires = cert.expires.as_deref()?;
+                        NaiveDateTime::parse_from_str(expires, "%Y%m%d%H%M%SZ").ok().map(|date| date.and_utc())
+                    }),
+                    versions: ssl.versions.into_iter().filter(|version| !version.starts_with('-')).collect(),
+                    cipher: ssl.cipher.and_then(|cipher| cipher.name),
+                    fingerprint: cert.and_then(|mut cert| cert.fingerprint.remove("sha256")),
+                    jarm: ssl.jarm,
+                }
+            }),
+            // CPE 2.3 quando disponível, senão o formato legado (`cpe:/a:...`)
+            cpes: if self.cpe23.is_empty() { self.cpe } else { self.cpe23 },
+            cves,
+            last_seen: self.timestamp.as_deref().and_then(parse_timestamp),
+        }
+    }
+}
+
+// Mantém um serviço por (ip, porta, transporte)
+//
+// Os serviços dos detalhes do host entram antes dos da busca, então a versão
+// mais completa (ex.: "nginx 1.25.3" em vez de "nginx") é a que fica.
+fn dedup_services(services: &mut Vec<Service>) {
+    let mut seen = HashSet::new();
+    services.retain(|service| seen.insert((service.ip.clone(), service.port, service.transport.clone())));
+}
+
+// Emissor no mesmo formato usado pelas fontes de CT (`C=US, O=..., CN=...`)
+fn distinguished_name(fields: &HashMap<String, String>) -> Option<String> {
+    let parts: Vec<String> = ["C", "O", "CN"]
+        .iter()
+        .filter_map(|key| fields.get(*key).map(|value| format!("{}={}", key, value)))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+// Horário da coleta, sem fuso (UTC) e com microssegundos
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+// Agrupa os registros de `/dns/domain` por nome, fora o próprio domínio; o IP
+// é o primeiro registro A (ou AAAA) e os CNAMEs seguem a ordem da resposta
+fn group_dns_entries(domain: &str, entries: Vec<ShodanDnsEntry>) -> Vec<Subdomain> {
+    let mut names: BTreeMap<String, Subdomain> = BTreeMap::new();
+
+    for entry in entries {
+        let label = entry.subdomain.trim().trim_end_matches('.').to_lowercase();
+        if label.is_empty() {
+            continue;
+        }
+        let (label, wildcard) = match label.strip_prefix("*.") {
+            Some(rest) => (rest.to_string(), true),
+            None if label == "*" => continue,
+            None => (label, false),
+        };
+
+        let name = format!("{}.{}", label, domain);
+        let subdomain = names.entry(name.clone()).or_insert_with(|| Subdomain {
+            name,
+            ip: None,
+            cnames: Vec::new(),
+            wildcard: false,
+            first_seen: None,
+            last_seen: None,
+            issuer: None,
+            certificates: Vec::new(),
+            records: Vec::new(),
+            source: DNS_SOURCE.to_string(),
+            sources: Vec::new(),
+        });
+
+        let record_type = entry.record_type.to_uppercase();
+        let value = entry.value.trim().trim_end_matches('.').to_string();
+        if record_type == "CNAME" && !subdomain.cnames.contains(&value) {
+            subdomain.cnames.push(value.clone());
+        }
+        subdomain.wildcard |= wildcard;
+        subdomain.last_seen = subdomain.last_seen.max(entry.last_seen.as_deref().and_then(parse_timestamp));
+
+        let record = DnsRecord { record_type, value };
+        if !subdomain.records.contains(&record) {
+            subdomain.records.push(record);
+        }
+    }
+
+    names
+        .into_values()
+        .map(|mut subdomain| {
+            subdomain.ip = ["A", "AAAA"].iter().find_map(|wanted| {
+                subdomain.records
+                    .iter()
+                    .find(|record| record.record_type == *wanted)
+                    .map(|record| record.value.clone())
+            });
+            subdomain
+        })
+        .collect()
 }
 
 pub struct ShodanModule {
-    api_key: String,
-    client: reqwest::Client,
+    config: ShodanConfig,
+    http: HttpClient,
 }
 
 impl ShodanModule {
-    pub fn new(api_key: String) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(15))
-            .build()
-            .unwrap_or_default();
-            
-        Self { api_key, client }
+    pub fn new(config: ShodanConfig, http: HttpClient) -> Self {
+        Self { config, http }
     }
     
-    // Função auxiliar para realizar consultas em paralelo
-    async fn search_shodan(&self, target: &str, is_ip: bool) -> Result<ShodanResponse> {
-        // Determina a consulta base
-        let base_query = if is_ip {
-            format!("ip:{}", target)
-        } else {
-            format!("hostname:{}", target)
-        };
-        
+    fn base_url(&self) -> &str {
+        self.config.base_url.trim_end_matches('/')
+    }
+    
+    // Saldo de créditos da conta; a consulta é gratuita
+    async fn api_info(&self) -> Option<ShodanApiInfo> {
+        let url = format!("{}/api-info?key={}", self.base_url(), self.config.api_key);
+
+        let response = self.http
+            .get(&url, Duration::from_secs(self.config.search_timeout_secs))
+            .await
+            .and_then(|response| Ok(response.error_for_status()?));
+        match response {
+            Ok(response) => response.json().ok(),
+            Err(e) => {
+                tracing::warn!("Shodan: saldo de créditos indisponível: {:#}", e);
+                None
+            }
+        }
+    }
+
+    // Resumo da exposição por facetas; não gasta créditos de consulta
+    async fn count(&self, query: &str) -> Result<Vec<FacetCount>> {
+        let url = format!(
+            "{}/shodan/host/count?key={}&query={}&facets={}",
+            self.base_url(),
+            self.config.api_key,
+            urlencoding::encode(query),
+            urlencoding::encode(&self.config.facets.join(","))
+        );
+
+        let counts: ShodanCountResponse = self.http
+            .get(&url, Duration::from_secs(self.config.search_timeout_secs))
+            .await
+            .context("Falha ao consultar Shodan")?
+            .error_for_status()?
+            .json()
+            .context("Falha ao parsear contagem do Shodan")?;
+
+        tracing::info!("Shodan: {} hosts para {}", counts.total.unwrap_or_default(), query);
+
+        // Na ordem configurada, cada faceta com os valores mais frequentes primeiro
+        let mut facets = counts.facets;
+        Ok(self.config.facets
+            .iter()
+            .filter_map(|facet| facets.remove_entry(facet))
+            .flat_map(|(facet, buckets)| {
+                buckets.into_iter().map(move |bucket| FacetCount {
+                    facet: facet.clone(),
+                    value: match bucket.value {
+                        serde_json::Value::String(value) => value,
+                        value => value.to_string(),
+                    },
+                    count: bucket.count,
+                    source: self.name().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    // Consulta um endpoint de DNS e interpreta o JSON da resposta
+    async fn dns_get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.http
+            .get(url, Duration::from_secs(self.config.search_timeout_secs))
+            .await
+            .context("Falha ao consultar Shodan DNS")?
+            .error_for_status()?
+            .json()
+            .context("Falha ao parsear resposta de DNS do Shodan")
+    }
+
+    // Subdomínios e registros conhecidos do domínio; cada página custa um crédito
+    async fn dns_domain(&self, domain: &str, budget: &CreditBudget) -> Result<Vec<Subdomain>> {
+        let mut entries = Vec::new();
+
+        for page in 1..=self.config.max_pages.max(1) {
+            if !budget.spend() {
+                if page == 1 {
+                    return Err(anyhow!("Orçamento de créditos de consulta esgotado"));
+                }
+                break;
+            }
+
+            let mut url = format!("{}/dns/domain/{}?key={}", self.base_url(), domain, self.config.api_key);
+            if page > 1 {
+                url.push_str(&format!("&page={}", page));
+            }
+
+            // Uma página seguinte que falha não descarta as anteriores
+            let response: ShodanDomainResponse = match self.dns_get(&url).await {
+                Ok(response) => response,
+                Err(e) if page > 1 => {
+                    tracing::warn!("Shodan: página {} do DNS de {} falhou: {}", page, domain, e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+
+            entries.extend(response.data);
+            if !response.more {
+                break;
+            }
+        }
+
+        let mut subdomains = group_dns_entries(domain, entries);
+        self.resolve(&mut subdomains).await;
+
+        Ok(subdomains)
+    }
+
+    // Completa o IP dos nomes que vieram sem registro A/AAAA; gratuito
+    async fn resolve(&self, subdomains: &mut [Subdomain]) {
+        let names: Vec<String> = subdomains
+            .iter()
+            .filter(|subdomain| subdomain.ip.is_none())
+            .map(|subdomain| subdomain.name.clone())
+            .collect();
+
+        let mut resolved: HashMap<String, String> = HashMap::new();
+        for batch in names.chunks(DNS_BATCH_SIZE) {
+            let url = format!(
+                "{}/dns/resolve?hostnames={}&key={}",
+                self.base_url(),
+                urlencoding::encode(&batch.join(",")),
+                self.config.api_key
+            );
+            match self.dns_get::<HashMap<String, Option<String>>>(&url).await {
+                Ok(answers) => resolved.extend(answers.into_iter().filter_map(|(name, ip)| Some((name, ip?)))),
+                Err(e) => tracing::warn!("Shodan: resolução de {} nomes falhou: {}", batch.len(), e),
+            }
+        }
+
+        for subdomain in subdomains.iter_mut() {
+            let Some(ip) = resolved.remove(&subdomain.name) else {
+                continue;
+            };
+            let record_type = match ip.parse::<IpAddr>() {
+                Ok(IpAddr::V6(_)) => "AAAA",
+                _ => "A",
+            };
+            subdomain.records.push(DnsRecord { record_type: record_type.to_string(), value: ip.clone() });
+            subdomain.ip = Some(ip);
+        }
+    }
+
+    // Nomes reversos de cada IP; gratuito
+    async fn reverse(&self, ips: &[String]) -> HashMap<String, Vec<String>> {
+        let mut names = HashMap::new();
+
+        for batch in ips.chunks(DNS_BATCH_SIZE) {
+            let url = format!(
+                "{}/dns/reverse?ips={}&key={}",
+                self.base_url(),
+                urlencoding::encode(&batch.join(",")),
+                self.config.api_key
+            );
+            match self.dns_get::<HashMap<String, Option<Vec<String>>>>(&url).await {
+                Ok(answers) => names.extend(answers.into_iter().filter_map(|(ip, hostnames)| Some((ip, hostnames?)))),
+                Err(e) => tracing::warn!("Shodan: DNS reverso de {} IPs falhou: {}", batch.len(), e),
+            }
+        }
+
+        names
+    }
+
+    // Busca uma página de resultados; a primeira vai sem o parâmetro `page`
+    async fn search_page(&self, query: &str, page: usize, budget: &CreditBudget) -> Result<ShodanResponse> {
+        if !budget.spend() {
+            return Err(anyhow!("Orçamento de créditos de consulta esgotado"));
+        }
+
+        let mut url = format!(
+            "{}/shodan/host/search?key={}&query={}",
+            self.base_url(),
+            self.config.api_key,
+            urlencoding::encode(query)
+        );
+        if page > 1 {
+            url.push_str(&format!("&page={}", page));
+        }
+
+        self.http
+            .get(&url, Duration::from_secs(self.config.search_timeout_secs))
+            .await
+            .context("Falha ao consultar Shodan")?
+            .error_for_status()?
+            .json()
+            .context("Falha ao parsear resposta do Shodan")
+    }
+
+    // Segue a paginação de uma consulta até `max_pages` ou o fim dos resultados
+    async fn search_query(&self, query: &str, budget: &CreditBudget) -> Result<ShodanResponse> {
+        let mut combined = self.search_page(query, 1, budget).await?;
+        let mut matches = combined.matches.take().unwrap_or_default();
+
+        let mut page = 1;
+        let mut last_page_size = matches.len();
+        while page < self.config.max_pages
+            && last_page_size >= PAGE_SIZE
+            && combined.total.is_none_or(|total| matches.len() < total)
+        {
+            page += 1;
+            // Uma página seguinte que falha não descarta as anteriores
+            match self.search_page(query, page, budget).await {
+                Ok(response) => {
+                    let page_matches = response.matches.unwrap_or_default();
+                    last_page_size = page_matches.len();
+                    matches.extend(page_matches);
+                }
+                Err(e) => {
+                    tracing::warn!("Shodan: página {} de {} falhou: {}", page, query, e);
+                    break;
+                }
+            }
+        }
+
+        if combined.total.is_some_and(|total| matches.len() < total) {
+            tracing::info!(
+                "Shodan: {} de {} resultados obtidos para {} ({} páginas)",
+                matches.len(),
+                combined.total.unwrap_or_default(),
+                query,
+                page
+            );
+        }
+
+        combined.matches = Some(matches);
+        Ok(combined)
+    }
+
+    // Função auxiliar para realizar consultas em paralelo; devolve também a
+    // descrição das consultas que falharam
+    async fn search_shodan(
+        &self,
+        target: &Target,
+        base_query: &str,
+        budget: &CreditBudget,
+    ) -> Result<(ShodanResponse, Vec<String>)> {
         // Consultas adicionais para expandir os resultados
-        let queries = vec![
-            base_query.clone(),
+        let queries = [
+            base_query.to_string(),
             format!("{}+port:80,443,8080,8443", base_query),
             format!("{}+has:web", base_query),
         ];
         
         // Executa as consultas em paralelo com timeout
-        let client = &self.client;
-        let api_key = &self.api_key;
+        let search_futures: Vec<_> = queries.iter().map(|query| self.search_query(query, budget)).collect();
         
-        let search_futures = queries.iter().map(|query| {
-            let url = format!(
-                "https://api.shodan.io/shodan/host/search?key={}&query={}",
-                api_key, 
-                urlencoding::encode(query)
-            );
-            let client = client.clone();
-            
-            async move {
-                let result = timeout(
-                    Duration::from_secs(10),
-                    client.get(&url).send()
-                ).await;
-                
-                match result {
-                    Ok(Ok(response)) => {
-                        if response.status().is_success() {
-                            match response.json::<ShodanResponse>().await {
-                                Ok(data) => Some(data),
-                                Err(_) => None,
-                            }
-                        } else {
-                            None
-                        }
-                    },
-                    _ => None,
+        let mut results = Vec::new();
+        let mut warnings = Vec::new();
+        let mut last_error = None;
+        let search_results: Vec<_> = stream::iter(search_futures)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await;
+        
+        for result in search_results {
+            match result {
+                Ok(data) => results.push(data),
+                Err(e) => {
+                    tracing::warn!("Shodan: consulta falhou para {}: {:#}", target, e);
+                    warnings.push(format!("{:#}", e));
+                    last_error = Some(e);
                 }
             }
-        });
+        }
         
-        let results = join_all(search_futures).await;
+        // Só falha se nenhuma das consultas teve sucesso
+        if results.is_empty() {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
         
-        // Combina os resultados de todas as consultas
+        // Combina os resultados de todas as consultas; as consultas se sobrepõem,
+        // então os totais não se somam (cada um já aparece no log de `search_query`)
         let mut combined = ShodanResponse {
             matches: Some(Vec::new()),
-            total: Some(0),
+            total: None,
         };
         
-        let mut seen_ips = HashSet::new();
+        // Um mesmo IP pode expor vários serviços: cada porta/transporte é mantido
+        let mut seen_services = HashSet::new();
         
-        for result in results.into_iter().flatten() {
+        for result in results {
             if let Some(matches) = result.matches {
                 for m in matches {
-                    if !seen_ips.contains(&m.ip_str) {
-                        seen_ips.insert(m.ip_str.clone());
+                    let transport = m.banner.transport.clone().unwrap_or_else(|| "tcp".to_string());
+                    if seen_services.insert((m.ip_str.clone(), m.banner.port, transport)) {
                         combined.matches.as_mut().unwrap().push(m);
                     }
                 }
             }
-            
-            if let Some(total) = result.total {
-                if let Some(ref mut combined_total) = combined.total {
-                    *combined_total += total;
-                }
-            }
         }
         
-        Ok(combined)
+        Ok((combined, warnings))
     }
     
-    // Consulta detalhada para um IP específico
-    async fn get_host_details(&self, ip: String) -> Option<ShodanHostResponse> {
+    // Consulta detalhada para um IP específico; `None` para IPs sem informação
+    async fn get_host_details(&self, ip: String) -> Result<Option<ShodanHostResponse>> {
         let url = format!(
-            "https://api.shodan.io/shodan/host/{}?key={}",
-            ip, self.api_key
+            "{}/shodan/host/{}?key={}",
+            self.base_url(),
+            ip,
+            self.config.api_key
         );
         
-        let result = timeout(
-            Duration::from_secs(8),
-            self.client.get(&url).send()
-        ).await;
+        let response = self.http
+            .get(&url, Duration::from_secs(self.config.host_timeout_secs))
+            .await
+            .context("Falha ao consultar detalhes do host no Shodan")?;
         
-        match result {
-            Ok(Ok(response)) => {
-                if response.status().is_success() {
-                    response.json::<ShodanHostResponse>().await.ok()
-                } else {
-                    None
-                }
-            },
-            _ => None,
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
         }
+        
+        response
+            .error_for_status()?
+            .json()
+            .map(Some)
+            .context("Falha ao parsear detalhes do host no Shodan")
     }
 }
 
@@ -171,28 +713,95 @@ impl OsintModule for ShodanModule {
         "Shodan"
     }
     
-    async fn scan(&self, target: &str) -> Result<ModuleResult> {
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
         let mut result = ModuleResult::default();
         
         // Verifica se temos uma API key
-        if self.api_key.is_empty() {
+        if self.config.api_key.is_empty() {
             tracing::warn!("API key do Shodan não fornecida, pulando consulta");
-            return Ok(result);
+            return Err(ModuleSkipped("API key do Shodan não fornecida".to_string()).into());
         }
         
         tracing::info!("Consultando Shodan para {}", target);
         
-        // Determina se o target é um IP ou domínio
-        let is_ip = target.parse::<IpAddr>().is_ok();
+        // Consulta base conforme o tipo do alvo
+        let base_query = match (target, target.ip(), target.domain()) {
+            (Target::Cidr(net), _, _) => format!("net:{}", net),
+            (Target::Asn(asn), _, _) => format!("asn:AS{}", asn),
+            (_, Some(ip), _) => format!("ip:{}", ip),
+            (_, None, Some(domain)) => format!("hostname:{}", domain),
+            _ => {
+                return Err(ModuleSkipped(format!("Shodan não consulta alvos do tipo {}", target.kind())).into());
+            }
+        };
         
-        // Realiza a busca principal
-        let shodan_data = self.search_shodan(target, is_ip).await?;
+        let account = self.api_info().await;
+        if let Some(account) = &account {
+            tracing::info!(
+                "Shodan: {} créditos de consulta disponíveis (plano {})",
+                account.query_credits.map_or_else(|| "?".to_string(), |credits| credits.to_string()),
+                account.plan.as_deref().unwrap_or("?")
+            );
+        }
+        let budget = CreditBudget::new(
+            self.config.credit_budget,
+            account.and_then(|account| account.query_credits),
+        );
+
+        // Sem créditos, o resumo por facetas ainda mostra a exposição
+        if self.config.mode == ShodanMode::Facets || !budget.available() {
+            if self.config.mode != ShodanMode::Facets {
+                tracing::warn!("Shodan: sem créditos de consulta para {}, usando apenas contagens", target);
+            }
+            result.facets = self.count(&base_query).await?;
+            result.usage = Some(budget.usage());
+            return Ok(result);
+        }
+
+        // Subdomínios e registros do domínio vêm antes das buscas, que dividem o
+        // restante do orçamento
+        let domain = target.domain();
+        let dns_subdomains = match domain {
+            Some(domain) if self.config.dns_lookups => match self.dns_domain(domain, &budget).await {
+                Ok(subdomains) => subdomains,
+                Err(e) => {
+                    tracing::warn!("Shodan: consulta de DNS falhou para {}: {:#}", domain, e);
+                    result.warnings.push(format!("DNS: {:#}", e));
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+
+        // Realiza a busca principal; com subdomínios do DNS, uma falha não
+        // descarta o que já foi encontrado
+        let shodan_data = match self.search_shodan(target, &base_query, &budget).await {
+            Ok((data, warnings)) => {
+                result.warnings.extend(warnings);
+                data
+            }
+            Err(e) if !dns_subdomains.is_empty() => {
+                tracing::warn!("Shodan: buscas falharam para {}: {:#}", target, e);
+                result.warnings.push(format!("{:#}", e));
+                ShodanResponse { matches: None, total: None }
+            }
+            Err(e) => return Err(e),
+        };
+        result.usage = Some(budget.usage());
+        
+        for subdomain in &dns_subdomains {
+            if let Some(ip) = &subdomain.ip {
+                result.ips.push(ip.clone());
+            }
+        }
+        result.subdomains.extend(dns_subdomains);
         
         // Mapeia de IP para hostnames/domínios para extrair subdomínios
         let mut ip_hostnames_map: HashMap<String, Vec<String>> = HashMap::new();
         
-        // Processamento inicial e coleta de IPs únicos
-        let mut unique_ips = HashSet::new();
+        // Processamento inicial e coleta de IPs únicos, em ordem para que os
+        // detalhes consultados sejam sempre os mesmos
+        let mut unique_ips = BTreeSet::new();
         
         if let Some(matches) = &shodan_data.matches {
             for m in matches {
@@ -202,27 +811,37 @@ impl OsintModule for ShodanModule {
                 if let Some(hostnames) = &m.hostnames {
                     ip_hostnames_map
                         .entry(m.ip_str.clone())
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .extend(hostnames.iter().cloned());
                 }
             }
         }
         
-        // Consulta detalhes para cada IP em paralelo (limitado a 10 IPs para evitar sobrecarga)
+        // Consulta detalhes para cada IP em paralelo (limitado para evitar sobrecarga)
         let ips_to_query: Vec<String> = unique_ips.iter()
-            .take(10)
+            .take(self.config.max_host_lookups)
             .cloned()
             .collect();
             
-        let detail_futures = ips_to_query.iter().map(|ip| {
+        let detail_futures: Vec<_> = ips_to_query.iter().map(|ip| {
             let ip_clone = ip.clone();
             self.get_host_details(ip_clone)
-        });
+        }).collect();
         
-        let details_results = join_all(detail_futures).await;
+        // `buffered` preserva a ordem, necessária para associar cada detalhe ao seu IP
+        let details_results: Vec<_> = stream::iter(detail_futures)
+            .buffered(self.config.concurrency.max(1))
+            .collect()
+            .await;
         
         // Processa os detalhes para enriquecer os resultados
-        for (i, detail_opt) in details_results.into_iter().enumerate() {
+        for (i, detail_result) in details_results.into_iter().enumerate() {
+            // Sem os detalhes de um IP, os dados da busca continuam valendo
+            let detail_opt = detail_result.unwrap_or_else(|e| {
+                tracing::warn!("Shodan: detalhes de {} indisponíveis: {:#}", ips_to_query[i], e);
+                result.warnings.push(format!("Detalhes de {}: {:#}", ips_to_query[i], e));
+                None
+            });
             if let Some(detail) = detail_opt {
                 let ip = &ips_to_query[i];
                 
@@ -230,26 +849,14 @@ impl OsintModule for ShodanModule {
                 if let Some(hostnames) = detail.hostnames {
                     ip_hostnames_map
                         .entry(ip.clone())
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .extend(hostnames);
                 }
                 
                 // Processa serviços detalhados
                 if let Some(data_details) = detail.data {
                     for service_detail in data_details {
-                        let service_name = match (service_detail.product, service_detail.version) {
-                            (Some(prod), Some(ver)) => format!("{} {}", prod, ver),
-                            (Some(prod), None) => prod,
-                            (None, _) => service_detail.transport.unwrap_or_else(|| "unknown".to_string()),
-                        };
-                        
-                        result.services.push(Service {
-                            ip: ip.clone(),
-                            port: service_detail.port,
-                            service: service_name,
-                            banner: service_detail.data,
-                            source: self.name().to_string(),
-                        });
+                        result.services.push(service_detail.into_service(ip.clone(), self.name()));
                     }
                 }
             }
@@ -261,53 +868,86 @@ impl OsintModule for ShodanModule {
                 // Adiciona IP à lista se ainda não estiver presente
                 result.ips.push(m.ip_str.clone());
                 
-                // Prepara o nome do serviço
-                let service_name = match (m.product, m.version) {
-                    (Some(prod), Some(ver)) => format!("{} {}", prod, ver),
-                    (Some(prod), None) => prod,
-                    (None, _) => m.transport.unwrap_or_else(|| "unknown".to_string()),
-                };
-                
                 // Adiciona o serviço à lista de resultados
-                result.services.push(Service {
-                    ip: m.ip_str,
-                    port: m.port,
-                    service: service_name,
-                    banner: m.data,
-                    source: self.name().to_string(),
-                });
+                result.services.push(m.banner.into_service(m.ip_str, self.name()));
             }
         }
         
-        // Processa subdomínios a partir dos hostnames mapeados
+        // Em alvos de rede todos os nomes dos IPs encontrados são mantidos; o
+        // próprio domínio também entra, para que o escopo mantenha o seu IP
+        let wanted = |hostname: &str| match domain {
+            Some(domain) => is_within(hostname, domain),
+            None => true,
+        };
+
+        // Nomes reversos dos IPs encontrados, antes dos hostnames dos banners
+        if self.config.dns_lookups && !unique_ips.is_empty() {
+            let ips: Vec<String> = unique_ips.iter().cloned().collect();
+            let mut reverse: Vec<(String, Vec<String>)> = self.reverse(&ips).await.into_iter().collect();
+            reverse.sort_unstable();
+
+            for (ip, hostnames) in reverse {
+                for hostname in hostnames {
+                    let hostname = hostname.trim_end_matches('.').to_lowercase();
+                    if wanted(&hostname) {
+                        result.subdomains.push(Subdomain {
+                            name: hostname,
+                            ip: Some(ip.clone()),
+                            cnames: Vec::new(),
+                            wildcard: false,
+                            first_seen: None,
+                            last_seen: None,
+                            issuer: None,
+                            certificates: Vec::new(),
+                            records: Vec::new(),
+                            source: DNS_SOURCE.to_string(),
+                            sources: Vec::new(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Processa subdomínios a partir dos hostnames mapeados, agrupados por IP
         for (ip, hostnames) in ip_hostnames_map {
             for hostname in hostnames {
-                if hostname.contains(target) && hostname != target {
+                if wanted(&hostname) {
                     result.subdomains.push(Subdomain {
                         name: hostname,
                         ip: Some(ip.clone()),
+                        cnames: Vec::new(),
+                        wildcard: false,
                         first_seen: None,
                         last_seen: None,
+                        issuer: None,
+                        certificates: Vec::new(),
+                        records: Vec::new(),
                         source: self.name().to_string(),
+                        sources: Vec::new(),
                     });
                 }
             }
         }
         
         // Remove duplicatas
-        let unique_ips: HashSet<_> = result.ips.drain(..).collect();
+        let unique_ips: BTreeSet<_> = result.ips.drain(..).collect();
         result.ips.extend(unique_ips);
         
-        let mut unique_services = HashSet::new();
-        result.services.retain(|service| {
-            let key = format!("{}-{}-{}", service.ip, service.port, service.service);
-            unique_services.insert(key)
-        });
+        dedup_services(&mut result.services);
         
-        let mut unique_subdomains = HashSet::new();
-        result.subdomains.retain(|subdomain| {
-            unique_subdomains.insert(subdomain.name.clone())
-        });
+        // Um nome visto no DNS e nos banners combina IP, registros e fontes
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut subdomains: Vec<Subdomain> = Vec::new();
+        for subdomain in result.subdomains.drain(..) {
+            match positions.get(&subdomain.name) {
+                Some(&position) => subdomains[position].absorb(subdomain),
+                None => {
+                    positions.insert(subdomain.name.clone(), subdomains.len());
+                    subdomains.push(subdomain);
+                }
+            }
+        }
+        result.subdomains = subdomains;
         
         tracing::info!(
             "Shodan: encontrados {} IPs, {} serviços e {} subdomínios para {}",
@@ -319,4 +959,136 @@ impl OsintModule for ShodanModule {
         
         Ok(result)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scan_uses_recorded_responses() {
+        let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/shodan"));
+        let config = ShodanConfig { api_key: "test-key".to_string(), ..Default::default() };
+        let result = ShodanModule::new(config, http).scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+        let mut ips = result.ips.clone();
+        ips.sort();
+        assert_eq!(ips, vec!["192.0.2.10", "192.0.2.20", "192.0.2.30", "2001:db8::10"]);
+
+        // A página de DNS e as três buscas contam como créditos, mesmo a que foi recusada
+        let usage = result.usage.unwrap();
+        assert_eq!((usage.used, usage.remaining, usage.skipped), (4, Some(100), 0));
+
+        let mut services: Vec<(&str, u16, &str)> = result.services
+            .iter()
+            .map(|s| (s.ip.as_str(), s.port, s.service.as_str()))
+            .collect();
+        services.sort();
+        assert_eq!(services, vec![
+            ("192.0.2.10", 22, "OpenSSH 9.6"),
+            ("192.0.2.10", 443, "nginx 1.25.3"),
+            ("192.0.2.20", 8080, "Jetty"),
+        ]);
+
+        let mut subdomains: Vec<(&str, Option<&str>)> = result.subdomains
+            .iter()
+            .map(|s| (s.name.as_str(), s.ip.as_deref()))
+            .collect();
+        subdomains.sort();
+        assert_eq!(subdomains, vec![
+            ("api.corp.example", Some("2001:db8::10")),
+            ("dev.corp.example", Some("192.0.2.20")),
+            ("mail.corp.example", Some("192.0.2.30")),
+            ("vpn.corp.example", Some("192.0.2.10")),
+            ("www.corp.example", Some("192.0.2.10")),
+        ]);
+
+        // O CNAME vem de `/dns/domain` e o IP de `/dns/resolve`
+        let mail = result.subdomains.iter().find(|s| s.name == "mail.corp.example").unwrap();
+        assert_eq!(mail.cnames, vec!["mx.mailhost.test".to_string()]);
+        let mut records: Vec<(&str, &str)> = mail.records
+            .iter()
+            .map(|record| (record.record_type.as_str(), record.value.as_str()))
+            .collect();
+        records.sort();
+        assert_eq!(records, vec![("A", "192.0.2.30"), ("CNAME", "mx.mailhost.test")]);
+        assert_eq!(mail.source, "Shodan DNS");
+
+        // Nome visto no DNS e nos banners junta as duas fontes
+        let www = result.subdomains.iter().find(|s| s.name == "www.corp.example").unwrap();
+        assert_eq!(www.source, "Shodan");
+        assert_eq!(www.sources, vec!["Shodan", "Shodan DNS"]);
+        assert_eq!(www.last_seen.unwrap().to_rfc3339(), "2024-03-01T12:00:00+00:00");
+    }
+
+    #[test]
+    fn dedup_keeps_each_transport_and_prefers_host_details() {
+        let service = |transport: &str, product: &str| Service {
+            ip: "192.0.2.53".to_string(),
+            port: 53,
+            transport: transport.to_string(),
+            service: product.to_string(),
+            source: "Shodan".to_string(),
+            ..Default::default()
+        };
+
+        // Detalhes do host primeiro, depois a busca
+        let mut services = vec![
+            service("udp", "BIND 9.18.24"),
+            service("tcp", "BIND 9.18.24"),
+            service("udp", "BIND"),
+            service("tcp", "BIND"),
+        ];
+        dedup_services(&mut services);
+
+        let kept: Vec<(&str, &str)> = services
+            .iter()
+            .map(|s| (s.transport.as_str(), s.service.as_str()))
+            .collect();
+        assert_eq!(kept, vec![("udp", "BIND 9.18.24"), ("tcp", "BIND 9.18.24")]);
+    }
+
+    #[test]
+    fn non_finite_cvss_scores_are_dropped() {
+        let banner: ShodanBanner = serde_json::from_value(serde_json::json!({
+            "port": 443,
+            "vulns": {
+                "CVE-2021-23017": { "cvss": "7.7", "verified": false },
+                "CVE-2000-0001": { "cvss": "NaN", "verified": false },
+                "CVE-2000-0002": { "cvss": "inf", "verified": false },
+            },
+        })).unwrap();
+        let service = banner.into_service("192.0.2.10".to_string(), "Shodan");
+
+        let cves: Vec<(&str, Option<f64>)> = service.cves.iter().map(|cve| (cve.id.as_str(), cve.cvss)).collect();
+        assert_eq!(cves, vec![
+            ("CVE-2021-23017", Some(7.7)),
+            ("CVE-2000-0001", None),
+            ("CVE-2000-0002", None),
+        ]);
+    }
+
+    #[test]
+    fn vulnerability_equality_matches_its_ordering() {
+        let vulnerability = |cvss: f64| Vulnerability {
+            id: "CVE-2021-23017".to_string(),
+            cvss: Some(cvss),
+            verified: false,
+            summary: None,
+        };
+
+        // `total_cmp` distingue 0.0 de -0.0; a igualdade precisa concordar
+        let (positive, negative) = (vulnerability(0.0), vulnerability(-0.0));
+        assert_eq!(positive.cmp(&negative) == std::cmp::Ordering::Equal, positive == negative);
+        assert_ne!(positive, negative);
+        assert_eq!(vulnerability(7.7), vulnerability(7.7));
+    }
+
+    #[test]
+    fn services_without_transport_default_to_tcp() {
+        let service: Service = serde_json::from_str(
+            r#"{"ip":"192.0.2.10","port":22,"service":"OpenSSH","banner":null,"source":"Shodan"}"#,
+        ).unwrap();
+        assert_eq!(service.transport, "tcp");
+    }
+}
@@ -1,37 +1,24 @@
-use crate::{OsintModule, types::{Url, ModuleResult}};
+use crate::{OsintModule, config::WaybackConfig, http::HttpClient, target::Target, types::{Url, ModuleResult}};
 use anyhow::{Result, Context};
 use async_trait::async_trait;
-use serde::Deserialize;
 use chrono::{DateTime, Utc, NaiveDateTime, TimeZone};
 use url::Url as ParsedUrl;
-use std::collections::HashMap;
+use std::time::Duration;
 
-// Estruturas para deserialização da API do Wayback Machine
-#[derive(Debug, Deserialize)]
-struct WaybackResponse {
-    url: Option<String>,
-    archived_snapshots: HashMap<String, WaybackSnapshot>,
+pub struct WaybackModule {
+    config: WaybackConfig,
+    http: HttpClient,
 }
 
-#[derive(Debug, Deserialize)]
-struct WaybackSnapshot {
-    url: String,
-    timestamp: String,
-    status: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct WaybackCdxRecord {
-    original: String,
-    timestamp: String,
-    statuscode: Option<String>,
+impl Default for WaybackModule {
+    fn default() -> Self {
+        Self::new(WaybackConfig::default(), HttpClient::default())
+    }
 }
 
-pub struct WaybackModule;
-
 impl WaybackModule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: WaybackConfig, http: HttpClient) -> Self {
+        Self { config, http }
     }
     
     // Função auxiliar para converter timestamp para DateTime
@@ -62,33 +49,29 @@ impl OsintModule for WaybackModule {
         "Wayback Machine"
     }
     
-    async fn scan(&self, target: &str) -> Result<ModuleResult> {
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
+        let target = target.require_domain(self.name())?;
         let mut result = ModuleResult::default();
         
         tracing::info!("Consultando Wayback Machine para {}", target);
         
         // Constrói a URL de consulta CDX (mais completa)
         let cdx_url = format!(
-            "http://web.archive.org/cdx/search/cdx?url=*.{}&output=json&collapse=urlkey&limit=500",
-            target
+            "{}/cdx/search/cdx?url=*.{}&output=json&collapse=urlkey&limit={}",
+            self.config.base_url.trim_end_matches('/'),
+            target,
+            self.config.limit
         );
         
         // Realiza a requisição
-        let client = reqwest::Client::new();
-        let response = client.get(&cdx_url)
-            .send()
+        let response = self.http.get(&cdx_url, Duration::from_secs(self.config.timeout_secs))
             .await
-            .context("Falha ao consultar Wayback CDX API")?;
-        
-        if !response.status().is_success() {
-            tracing::warn!("Wayback retornou status {}", response.status());
-            return Ok(result);
-        }
+            .context("Falha ao consultar Wayback CDX API")?
+            .error_for_status()?;
         
         // Parse da resposta JSON
         let cdx_data: Vec<Vec<String>> = response
             .json()
-            .await
             .context("Falha ao parsear resposta do Wayback CDX")?;
         
         // CDX API retorna a primeira linha como cabeçalho
@@ -152,4 +135,32 @@ impl OsintModule for WaybackModule {
         
         Ok(result)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scan_uses_recorded_responses() {
+        let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/wayback"));
+        let result = WaybackModule::new(WaybackConfig::default(), http)
+            .scan(&"corp.example".parse().unwrap())
+            .await
+            .unwrap();
+
+        let urls: Vec<(&str, Option<u16>)> = result.urls
+            .iter()
+            .map(|u| (u.url.as_str(), u.status_code))
+            .collect();
+        assert_eq!(urls, vec![
+            ("http://corp.example/robots.txt", None),
+            ("https://corp.example/", Some(200)),
+            ("https://www.corp.example/login", Some(302)),
+        ]);
+        assert_eq!(
+            result.urls[1].last_seen.unwrap().to_rfc3339(),
+            "2023-01-05T10:11:12+00:00"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::types::{Confidence, Finding, ModuleStatus, NetworkKind, TargetResponse};
+use std::fmt::Write;
+
+// Certificados que expiram dentro deste prazo são destacados
+const EXPIRING_DAYS: i64 = 30;
+
+// CVEs listadas por serviço na tabela
+const MAX_LISTED_CVES: usize = 3;
+
+// Renderiza o resultado de um escaneamento como tabelas em texto
+pub fn render_table(response: &TargetResponse) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Alvo: {} ({})", response.target, response.timestamp.to_rfc3339());
+
+    push_section(
+        &mut out,
+        "Módulos",
+        &["MÓDULO", "STATUS", "DURAÇÃO", "DETALHES"],
+        response.modules.iter().map(|report| {
+            let status = match report.status {
+                ModuleStatus::Ok => "ok",
+                ModuleStatus::Partial => "parcial",
+                ModuleStatus::Skipped => "ignorado",
+                ModuleStatus::Failed => "falhou",
+                ModuleStatus::TimedOut => "tempo esgotado",
+            };
+            let mut details = report.error.clone().unwrap_or_else(|| {
+                let counts = report.counts;
+                format!(
+                    "{} subdomínios, {} IPs, {} serviços, {} URLs, {} dorks, {} certificados",
+                    counts.subdomains,
+                    counts.ips,
+                    counts.services,
+                    counts.urls,
+                    counts.dorks,
+                    counts.certificates
+                )
+            });
+            if let Some(usage) = report.usage {
+                let _ = write!(details, "; {} créditos usados", usage.used);
+                if let Some(remaining) = usage.remaining {
+                    let _ = write!(details, ", {} restantes", remaining.saturating_sub(usage.used));
+                }
+                if usage.skipped > 0 {
+                    let _ = write!(details, ", {} consultas puladas pelo orçamento", usage.skipped);
+                }
+            }
+            if !report.warnings.is_empty() {
+                let _ = write!(details, "; falhas parciais: {}", report.warnings.join("; "));
+            }
+            vec![
+                report.name.clone(),
+                status.to_string(),
+                format!("{}ms", report.duration_ms),
+                details,
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "Subdomínios",
+        &["NOME", "IP", "CNAME", "FONTE"],
+        response.subdomains.iter().map(|subdomain| {
+            vec![
+                subdomain.name.clone(),
+                subdomain.ip.clone().unwrap_or_else(|| "-".to_string()),
+                if subdomain.cnames.is_empty() {
+                    "-".to_string()
+                } else {
+                    subdomain.cnames.join(" -> ")
+                },
+                subdomain.sources().join(", "),
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "IPs",
+        &["IP", "ASN", "ORGANIZAÇÃO", "PAÍS", "PROVEDOR"],
+        response.ips.iter().map(|info| {
+            let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+            // Bordas de CDN ficam evidentes para não serem confundidas com a infraestrutura do alvo
+            let provider = match (&info.provider, info.kind) {
+                (Some(provider), Some(NetworkKind::Cdn)) => format!("{} (CDN)", provider),
+                (Some(provider), Some(NetworkKind::Hosting)) => format!("{} (hospedagem)", provider),
+                (provider, _) => or_dash(provider.as_deref()),
+            };
+            vec![
+                info.ip.clone(),
+                info.asn.map(|asn| format!("AS{}", asn)).unwrap_or_else(|| "-".to_string()),
+                or_dash(info.as_org.as_deref()),
+                or_dash(info.country.as_deref()),
+                provider,
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "Serviços",
+        &["IP", "PORTA", "SERVIÇO", "TÍTULO", "CVES", "FONTE"],
+        response.services.iter().map(|service| {
+            // As CVEs de maior nota primeiro; as demais apenas contadas
+            let mut cves: Vec<String> = service.cves
+                .iter()
+                .take(MAX_LISTED_CVES)
+                .map(|cve| match cve.cvss {
+                    Some(cvss) => format!("{} ({:.1})", cve.id, cvss),
+                    None => cve.id.clone(),
+                })
+                .collect();
+            if service.cves.len() > MAX_LISTED_CVES {
+                cves.push(format!("+{}", service.cves.len() - MAX_LISTED_CVES));
+            }
+            vec![
+                service.ip.clone(),
+                format!("{}/{}", service.port, service.transport),
+                service.service.clone(),
+                service.http.as_ref().and_then(|http| http.title.clone()).unwrap_or_else(|| "-".to_string()),
+                if cves.is_empty() { "-".to_string() } else { cves.join(", ") },
+                service.source.clone(),
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "URLs",
+        &["URL", "STATUS"],
+        response.urls.iter().map(|url| {
+            vec![
+                url.url.clone(),
+                url.status_code.map(|code| code.to_string()).unwrap_or_else(|| "-".to_string()),
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "Exposição",
+        &["FACETA", "VALOR", "HOSTS", "FONTE"],
+        response.facets.iter().map(|facet| {
+            vec![
+                facet.facet.clone(),
+                facet.value.clone(),
+                facet.count.to_string(),
+                facet.source.clone(),
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "Dorks",
+        &["CONSULTA", "DESCRIÇÃO"],
+        response.dorks.iter().map(|dork| vec![dork.query.clone(), dork.description.clone()]),
+    );
+
+    // O estado dos certificados é relativo ao momento do escaneamento
+    let now = response.timestamp;
+    push_section(
+        &mut out,
+        "Certificados",
+        &["ID", "EMISSOR", "VALIDADE", "ESTADO"],
+        response.certificates.iter().map(|certificate| {
+            let date = |date: Option<chrono::DateTime<chrono::Utc>>| {
+                date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "?".to_string())
+            };
+            let status = match certificate.not_after {
+                _ if certificate.is_expired(now) => "expirado".to_string(),
+                Some(not_after) if certificate.expires_within(now, chrono::Duration::days(EXPIRING_DAYS)) => {
+                    format!("expira em {} dias", (not_after - now).num_days())
+                }
+                Some(_) => "válido".to_string(),
+                None => "-".to_string(),
+            };
+            vec![
+                certificate.id.clone(),
+                certificate.issuer.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{} a {}", date(certificate.not_before), date(certificate.not_after)),
+                status,
+            ]
+        }),
+    );
+
+    push_section(
+        &mut out,
+        "Achados",
+        &["TIPO", "SUBDOMÍNIO", "PROVEDOR", "CONFIANÇA", "EVIDÊNCIA"],
+        response.findings.iter().map(|finding| match finding {
+            Finding::TakeoverCandidate(candidate) => {
+                let confidence = match candidate.confidence {
+                    Confidence::Low => "baixa",
+                    Confidence::Medium => "média",
+                    Confidence::High => "alta",
+                };
+                vec![
+                    "takeover".to_string(),
+                    candidate.subdomain.clone(),
+                    candidate.provider.clone(),
+                    confidence.to_string(),
+                    candidate.evidence.clone(),
+                ]
+            }
+        }),
+    );
+
+    out
+}
+
+// Adiciona uma seção com título e colunas alinhadas
+fn push_section(
+    out: &mut String,
+    title: &str,
+    headers: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) {
+    let rows: Vec<Vec<String>> = rows.collect();
+
+    let _ = writeln!(out, "\n{} ({})", title, rows.len());
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                // A última coluna não precisa de preenchimento
+                if i + 1 == row.len() {
+                    cell.clone()
+                } else {
+                    format!("{:width$}", cell, width = widths[i])
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "  {}", line.join("  "));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    OsintModule,
+    config::Config,
+    dns::DnsResolver,
+    http::{HttpClient, HttpError},
+    ipinfo::IpInfoLookup,
+    scope::Scope,
+    target::Target,
+    takeover::TakeoverDetector,
+    modules::{
+        crtsh::CrtShModule,
+        certspotter::CertSpotterModule,
+        shodan::ShodanModule,
+        wayback::WaybackModule,
+        dorker::DorkerModule,
+    },
+    types::{
+        ModuleReport,
+        ModuleResult,
+        ModuleSkipped,
+        ModuleStatus,
+        TargetResponse,
+    },
+};
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Tempo máximo padrão de execução de cada módulo
+const DEFAULT_MODULE_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Maior bloco CIDR aceito por padrão, em endereços
+const DEFAULT_MAX_NETWORK_HOSTS: usize = 256;
+
+// Registro dos módulos OSINT habilitados
+//
+// Construído uma única vez na inicialização e compartilhado via `AppState`.
+// Módulos externos (outras crates) podem ser adicionados com `register`.
+#[derive(Clone)]
+pub struct ModuleRegistry {
+    modules: Vec<Arc<dyn OsintModule>>,
+    module_timeout: Duration,
+    max_network_hosts: usize,
+    resolver: Option<Arc<DnsResolver>>,
+    takeover: Option<Arc<TakeoverDetector>>,
+    ipinfo: Option<Arc<IpInfoLookup>>,
+}
+
+impl Default for ModuleRegistry {
+    fn default() -> Self {
+        Self {
+            modules: Vec::new(),
+            module_timeout: DEFAULT_MODULE_TIMEOUT,
+            max_network_hosts: DEFAULT_MAX_NETWORK_HOSTS,
+            resolver: None,
+            takeover: None,
+            ipinfo: None,
+        }
+    }
+}
+
+impl ModuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Constrói o registro com os módulos nativos habilitados na configuração
+    pub fn from_config(config: &Config) -> Self {
+        let modules = &config.modules;
+        let http = HttpClient::from_config(&config.http);
+
+        let builtin: Vec<(bool, Arc<dyn OsintModule>)> = vec![
+            (modules.crtsh.enabled, Arc::new(CrtShModule::new(modules.crtsh.clone(), http.clone()))),
+            (modules.certspotter.enabled, Arc::new(CertSpotterModule::new(modules.certspotter.clone(), http.clone()))),
+            (modules.shodan.enabled, Arc::new(ShodanModule::new(modules.shodan.clone(), http.clone()))),
+            (modules.wayback.enabled, Arc::new(WaybackModule::new(modules.wayback.clone(), http.clone()))),
+            (modules.dorker.enabled, Arc::new(DorkerModule::new())),
+        ];
+
+        let mut registry = Self::new()
+            .with_timeout(Duration::from_secs(config.scan.module_timeout_secs))
+            .with_network_limit(config.scan.max_network_hosts);
+
+        for (enabled, module) in builtin {
+            if !enabled {
+                tracing::info!("Módulo {} desabilitado por configuração", module.name());
+                continue;
+            }
+            registry.register(module);
+        }
+
+        if config.dns.enabled {
+            match DnsResolver::from_config(&config.dns) {
+                Ok(resolver) => registry = registry.with_resolver(resolver),
+                Err(e) => tracing::error!("Resolução DNS desabilitada: {:#}", e),
+            }
+        }
+
+        if config.dns.enabled && config.takeover.enabled {
+            match TakeoverDetector::from_config(&config.takeover, http) {
+                Ok(detector) => registry = registry.with_takeover(detector),
+                Err(e) => tracing::error!("Detecção de takeover desabilitada: {:#}", e),
+            }
+        }
+
+        if config.dns.enabled && config.ipinfo.enabled {
+            match IpInfoLookup::from_config(&config.ipinfo) {
+                Ok(lookup) => registry = registry.with_ipinfo(lookup),
+                Err(e) => tracing::error!("Identificação de ASN desabilitada: {:#}", e),
+            }
+        }
+
+        registry
+    }
+
+    // Define o tempo máximo de execução de cada módulo
+    pub fn with_timeout(mut self, module_timeout: Duration) -> Self {
+        self.module_timeout = module_timeout;
+        self
+    }
+
+    // Define o maior bloco CIDR aceito como alvo
+    pub fn with_network_limit(mut self, max_network_hosts: usize) -> Self {
+        self.max_network_hosts = max_network_hosts;
+        self
+    }
+
+    pub fn max_network_hosts(&self) -> usize {
+        self.max_network_hosts
+    }
+
+    // Habilita a resolução DNS dos subdomínios após a coleta dos módulos
+    pub fn with_resolver(mut self, resolver: DnsResolver) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    // Habilita a detecção de candidatos a takeover sobre os CNAMEs resolvidos
+    pub fn with_takeover(mut self, detector: TakeoverDetector) -> Self {
+        self.takeover = Some(Arc::new(detector));
+        self
+    }
+
+    // Habilita a identificação de ASN, país e provedor dos IPs encontrados
+    pub fn with_ipinfo(mut self, lookup: IpInfoLookup) -> Self {
+        self.ipinfo = Some(Arc::new(lookup));
+        self
+    }
+
+    // Adiciona um módulo ao registro
+    pub fn register(&mut self, module: Arc<dyn OsintModule>) {
+        self.modules.push(module);
+    }
+
+    // Módulos registrados, na ordem de registro
+    pub fn modules(&self) -> &[Arc<dyn OsintModule>] {
+        &self.modules
+    }
+
+    // Executa um único módulo, medindo o tempo e classificando o resultado
+    //
+    // Dados fora do escopo são descartados antes de chegar à resposta.
+    pub async fn run_module(
+        &self,
+        module: &dyn OsintModule,
+        scope: &Scope,
+    ) -> (ModuleReport, Option<ModuleResult>) {
+        let target = scope.target();
+        let started = Instant::now();
+        let outcome = tokio::time::timeout(self.module_timeout, module.scan(target)).await;
+
+        let mut report = ModuleReport {
+            name: module.name().to_string(),
+            status: ModuleStatus::Ok,
+            error: None,
+            duration_ms: started.elapsed().as_millis() as u64,
+            counts: Default::default(),
+            usage: None,
+            http_error: None,
+            warnings: Vec::new(),
+        };
+
+        let data = match outcome {
+            Ok(Ok(mut data)) => {
+                let discarded = scope.apply(&mut data);
+                if discarded > 0 {
+                    tracing::debug!("Módulo {}: {} itens fora do escopo de {}", module.name(), discarded, target);
+                }
+                report.counts = data.counts();
+                report.usage = data.usage;
+                if !data.warnings.is_empty() {
+                    tracing::warn!(
+                        "Módulo {} concluiu parcialmente para {}: {}",
+                        module.name(),
+                        target,
+                        data.warnings.join("; ")
+                    );
+                    report.status = ModuleStatus::Partial;
+                    report.warnings = std::mem::take(&mut data.warnings);
+                }
+                Some(data)
+            },
+            Ok(Err(e)) => {
+                if e.downcast_ref::<ModuleSkipped>().is_some() {
+                    tracing::info!("Módulo {} ignorado para {}: {}", module.name(), target, e);
+                    report.status = ModuleStatus::Skipped;
+                } else {
+                    tracing::warn!("Módulo {} falhou para {}: {:#}", module.name(), target, e);
+                    report.status = ModuleStatus::Failed;
+                }
+                report.error = Some(describe_error(&e));
+                report.http_error = e.downcast_ref::<HttpError>().cloned();
+                None
+            },
+            Err(_) => {
+                tracing::warn!(
+                    "Módulo {} excedeu o tempo limite de {}s para {}",
+                    module.name(),
+                    self.module_timeout.as_secs(),
+                    target
+                );
+                report.status = ModuleStatus::TimedOut;
+                report.error = Some(format!(
+                    "Tempo limite de {}s excedido",
+                    self.module_timeout.as_secs()
+                ));
+                None
+            },
+        };
+
+        (report, data)
+    }
+
+    // Executa todos os módulos em paralelo, entregando cada um assim que termina
+    pub fn scan_each<'a>(
+        &'a self,
+        scope: &'a Scope,
+    ) -> impl Stream<Item = (ModuleReport, Option<ModuleResult>)> + 'a {
+        self.modules
+            .iter()
+            .map(|module| self.run_module(module.as_ref(), scope))
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    // Executa todos os módulos em paralelo e combina os resultados
+    pub async fn scan(&self, scope: &Scope) -> TargetResponse {
+        let mut response = TargetResponse::new(&scope.target().history_key());
+
+        let mut modules = self.scan_each(scope);
+        while let Some((report, data)) = modules.next().await {
+            response.record(report, data);
+        }
+
+        self.enrich(scope, &mut response).await;
+        self.finalize(&mut response);
+
+        response
+    }
+
+    // Resolve os subdomínios coletados, identifica a rede dos IPs e procura
+    // candidatos a takeover, se a resolução DNS estiver habilitada
+    pub async fn enrich(&self, scope: &Scope, response: &mut TargetResponse) {
+        let Some(resolver) = &self.resolver else {
+            return;
+        };
+
+        // Alvos de rede ganham os nomes reversos dos endereços do bloco e dos IPs encontrados
+        if scope.target().domain().is_none() {
+            let ips = self.network_ips(scope.target(), response);
+            let found = resolver.reverse_all(ips).await;
+            tracing::info!("DNS: {} nomes reversos (PTR) para {}", found.len(), response.target);
+            // Em ASNs, os IPs já aceitos na resposta vieram da busca pelo ASN
+            let known: HashSet<IpAddr> = response.ips.iter().filter_map(|info| info.ip.parse().ok()).collect();
+            response.subdomains.extend(
+                found.into_iter().filter(|subdomain| scope.allows_with_ips(subdomain, &known)),
+            );
+        }
+
+        let resolutions = resolver.enrich(response).await;
+
+        if let Some(lookup) = &self.ipinfo {
+            lookup.annotate(resolver, response).await;
+        }
+
+        if let Some(detector) = &self.takeover {
+            detector.detect(response, &resolutions).await;
+        }
+    }
+
+    // Endereços consultados por PTR, limitados ao tamanho máximo de bloco
+    fn network_ips(&self, target: &Target, response: &TargetResponse) -> Vec<IpAddr> {
+        let mut ips = target.hosts(self.max_network_hosts).unwrap_or_else(|e| {
+            tracing::warn!("PTR: bloco ignorado: {}", e);
+            Vec::new()
+        });
+        ips.extend(response.ips.iter().filter_map(|info| info.ip.parse::<IpAddr>().ok()));
+        ips.sort_unstable();
+        ips.dedup();
+        ips.truncate(self.max_network_hosts);
+        ips
+    }
+
+    // Ordena os relatórios na ordem de registro e remove duplicatas
+    pub fn finalize(&self, response: &mut TargetResponse) {
+        response.modules.sort_by_key(|report| {
+            self.modules
+                .iter()
+                .position(|module| module.name() == report.name)
+                .unwrap_or(usize::MAX)
+        });
+
+        response.dedup();
+    }
+}
+
+// Resume um erro com o contexto mais externo e a causa raiz
+fn describe_error(error: &anyhow::Error) -> String {
+    if error.chain().count() > 1 {
+        format!("{}: {}", error, error.root_cause())
+    } else {
+        error.to_string()
+    }
+}
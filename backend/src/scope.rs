@@ -0,0 +1,370 @@
+use crate::{target::Target, types::{ModuleResult, Subdomain}};
+use anyhow::{bail, Result};
+use publicsuffix::{List, Psl};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+// Public Suffix List distribuída com o binário (https://publicsuffix.org/list/)
+const PUBLIC_SUFFIX_LIST: &str = include_str!("../data/public_suffix_list.dat");
+
+fn public_suffixes() -> &'static List {
+    static LIST: OnceLock<List> = OnceLock::new();
+    LIST.get_or_init(|| PUBLIC_SUFFIX_LIST.parse().expect("Public Suffix List embutida inválida"))
+}
+
+// Indica se o nome é um sufixo público (ex.: `com`, `co.uk`, `github.io`)
+pub fn is_public_suffix(name: &str) -> bool {
+    public_suffixes()
+        .suffix(name.as_bytes())
+        .is_some_and(|suffix| suffix.as_bytes() == name.as_bytes())
+}
+
+// Compara rótulo a rótulo: `www.corp.com` pertence a `corp.com`, `evilcorp.com` não
+pub fn is_within(name: &str, domain: &str) -> bool {
+    name == domain
+        || name
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+// Normaliza um nome de certificado, indicando se era um wildcard (`*.`)
+//
+// Usado pelas fontes de CT para que `WWW.corp.com.` e `www.corp.com` sejam o
+// mesmo nome e entradas que não são hostnames fiquem de fora.
+pub fn normalize_name(raw: &str) -> Option<(String, bool)> {
+    let name = raw.trim().trim_end_matches('.').to_lowercase();
+    let (name, wildcard) = match name.strip_prefix("*.") {
+        Some(rest) => (rest.to_string(), true),
+        None => (name, false),
+    };
+
+    // Descarta e-mails, curingas no meio do nome e outras entradas que não são hostnames
+    let valid = !name.is_empty()
+        && name.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    valid.then_some((name, wildcard))
+}
+
+// Padrão de inclusão/exclusão: `corp.net` cobre o domínio e seus subdomínios,
+// `*.corp.net` apenas os subdomínios
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    domain: String,
+    subdomains_only: bool,
+}
+
+impl Pattern {
+    fn parse(raw: &str) -> Result<Self> {
+        let pattern = raw.trim().trim_end_matches('.').to_lowercase();
+        let (domain, subdomains_only) = match pattern.strip_prefix("*.") {
+            Some(domain) => (domain.to_string(), true),
+            None => (pattern, false),
+        };
+
+        let valid = !domain.is_empty()
+            && domain.split('.').all(|label| {
+                !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+        if !valid {
+            bail!("Padrão de escopo inválido: {}", raw.trim());
+        }
+
+        Ok(Self { domain, subdomains_only })
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        is_within(name, &self.domain) && !(self.subdomains_only && name == self.domain)
+    }
+}
+
+// Escopo de um escaneamento: o alvo, seus subdomínios e os padrões da requisição
+//
+// Aplicado a cada `ModuleResult` antes de ser combinado na resposta, para que
+// nenhum módulo traga nomes como `evilcorp.com` ou `corp.com.attacker.net`.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    target: Target,
+    // Domínio do alvo; alvos de rede (IP, CIDR, ASN) não têm
+    domain: Option<String>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Scope {
+    pub fn new(target: Target) -> Result<Self> {
+        let domain = target.domain().map(str::to_string);
+        if let Some(domain) = domain.as_deref().filter(|domain| is_public_suffix(domain)) {
+            bail!("{} é um sufixo público e não pode ser usado como alvo", domain);
+        }
+
+        Ok(Self { target, domain, include: Vec::new(), exclude: Vec::new() })
+    }
+
+    // Acrescenta domínios extras ao escopo e nomes a descartar
+    pub fn with_patterns(mut self, include: &[String], exclude: &[String]) -> Result<Self> {
+        for raw in include {
+            let pattern = Pattern::parse(raw)?;
+            // Incluir um sufixo público traria nomes de qualquer dono
+            if is_public_suffix(&pattern.domain) {
+                bail!("Padrão de inclusão abrangente demais: {}", raw.trim());
+            }
+            self.include.push(pattern);
+        }
+
+        for raw in exclude {
+            self.exclude.push(Pattern::parse(raw)?);
+        }
+
+        Ok(self)
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    // Verifica se um nome de host pertence ao escopo
+    pub fn contains(&self, name: &str) -> bool {
+        let name = name.trim_end_matches('.').to_lowercase();
+
+        let included = self.domain.as_deref().is_some_and(|domain| is_within(&name, domain))
+            || self.include.iter().any(|pattern| pattern.matches(&name));
+
+        included && !self.excludes(&name)
+    }
+
+    fn excludes(&self, name: &str) -> bool {
+        self.exclude.iter().any(|pattern| pattern.matches(name))
+    }
+
+    // Subdomínios entram pelo nome ou, em alvos de rede, pelo IP dentro do bloco
+    pub fn allows(&self, subdomain: &Subdomain) -> bool {
+        self.allows_with_ips(subdomain, &HashSet::new())
+    }
+
+    // Como `allows`, aceitando em alvos de ASN os IPs que a busca pelo ASN trouxe
+    //
+    // O ASN não é conferido localmente (`Target::covers`); sem esses IPs, nomes
+    // de alvos de ASN só entram pelos padrões de inclusão.
+    pub fn allows_with_ips(&self, subdomain: &Subdomain, found: &HashSet<IpAddr>) -> bool {
+        if self.contains(&subdomain.name) {
+            return true;
+        }
+
+        let on_target = subdomain.ip
+            .as_deref()
+            .and_then(|ip| ip.parse::<IpAddr>().ok())
+            .is_some_and(|ip| {
+                self.target.covers(ip) || (matches!(self.target, Target::Asn(_)) && found.contains(&ip))
+            });
+        on_target && !self.excludes(&subdomain.name.trim_end_matches('.').to_lowercase())
+    }
+
+    // Remove do resultado de um módulo tudo o que estiver fora do escopo
+    //
+    // IPs e serviços ficam quando o IP é de um subdomínio mantido ou está no
+    // alvo: um banner do Shodan com `evilcorp.com` não traz o seu IP junto.
+    pub fn apply(&self, result: &mut ModuleResult) -> usize {
+        let total = |result: &ModuleResult| {
+            result.subdomains.len()
+                + result.ips.len()
+                + result.services.len()
+                + result.urls.len()
+                + result.certificates.len()
+        };
+        let before = total(result);
+
+        let found: HashSet<IpAddr> = result.ips.iter().filter_map(|ip| ip.parse().ok()).collect();
+        result.subdomains.retain(|subdomain| self.allows_with_ips(subdomain, &found));
+
+        let resolved: HashSet<IpAddr> = result.subdomains
+            .iter()
+            .filter_map(|subdomain| subdomain.ip.as_deref()?.parse().ok())
+            .collect();
+        let kept = |ip: &str| {
+            ip.parse::<IpAddr>().is_ok_and(|ip| {
+                resolved.contains(&ip)
+                    || self.target.covers(ip)
+                    || (matches!(self.target, Target::Asn(_)) && found.contains(&ip))
+            })
+        };
+        result.ips.retain(|ip| kept(ip));
+        result.services.retain(|service| kept(&service.ip));
+
+        result.urls.retain(|url| {
+            url::Url::parse(&url.url)
+                .ok()
+                .and_then(|parsed| parsed.host_str().map(|host| self.contains(host)))
+                .unwrap_or(false)
+        });
+        result.certificates.retain(|certificate| {
+            certificate
+                .names
+                .iter()
+                .any(|name| self.contains(name.trim_start_matches("*.")))
+        });
+
+        before - total(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(target: &str) -> Result<Scope> {
+        Scope::new(target.parse().unwrap())
+    }
+
+    #[test]
+    fn normalize_name_flags_wildcards_and_drops_invalid_names() {
+        assert_eq!(normalize_name(" *.API.corp.example. "), Some(("api.corp.example".to_string(), true)));
+        assert_eq!(normalize_name("www.corp.example"), Some(("www.corp.example".to_string(), false)));
+        assert_eq!(normalize_name("admin@corp.example"), None);
+        assert_eq!(normalize_name("www.*.corp.example"), None);
+        assert_eq!(normalize_name(""), None);
+    }
+
+    #[test]
+    fn matches_whole_labels_only() {
+        let scope = scope("corp.com").unwrap();
+
+        assert!(scope.contains("corp.com"));
+        assert!(scope.contains("www.corp.com"));
+        assert!(scope.contains("A.B.Corp.Com."));
+        assert!(!scope.contains("evilcorp.com"));
+        assert!(!scope.contains("corp.com.attacker.net"));
+        assert!(!scope.contains("com"));
+    }
+
+    #[test]
+    fn rejects_public_suffix_targets() {
+        for target in ["com.br", "co.uk", "github.io", "https://github.io/corp", "dev@co.uk"] {
+            assert!(scope(target).is_err(), "{} aceito", target);
+        }
+        assert!(scope("corp.github.io").is_ok());
+        assert!(scope("corp.co.uk").is_ok());
+    }
+
+    #[test]
+    fn url_and_network_targets() {
+        let url = scope("https://www.corp.com/login").unwrap();
+        assert!(url.contains("api.www.corp.com"));
+        assert!(!url.contains("corp.com"));
+
+        // Sem domínio, apenas os padrões de inclusão trazem nomes
+        let network = scope("192.0.2.0/24").unwrap();
+        assert!(!network.contains("host.corp.com"));
+        let network = network.with_patterns(&["corp.com".to_string()], &[]).unwrap();
+        assert!(network.contains("host.corp.com"));
+    }
+
+    #[test]
+    fn network_targets_allow_names_by_ip() {
+        let subdomain = |name: &str, ip: Option<&str>| Subdomain {
+            name: name.to_string(),
+            ip: ip.map(str::to_string),
+            cnames: Vec::new(),
+            wildcard: false,
+            first_seen: None,
+            last_seen: None,
+            issuer: None,
+            certificates: Vec::new(),
+            records: Vec::new(),
+            source: "PTR".to_string(),
+            sources: Vec::new(),
+        };
+
+        let network = scope("192.0.2.0/24")
+            .unwrap()
+            .with_patterns(&[], &["*.cdn.test".to_string()])
+            .unwrap();
+        assert!(network.allows(&subdomain("mail.corp.test", Some("192.0.2.25"))));
+        assert!(!network.allows(&subdomain("mail.corp.test", Some("198.51.100.25"))));
+        assert!(!network.allows(&subdomain("mail.corp.test", None)));
+        assert!(!network.allows(&subdomain("edge.cdn.test", Some("192.0.2.26"))));
+
+        // Alvos de domínio continuam restritos ao nome
+        let domain = scope("corp.test").unwrap();
+        assert!(!domain.allows(&subdomain("evil.test", Some("192.0.2.25"))));
+
+        // Em ASNs, só os IPs trazidos pela busca do ASN contam
+        let asn = scope("AS64500").unwrap();
+        let found: HashSet<IpAddr> = HashSet::from(["192.0.2.25".parse().unwrap()]);
+        assert!(!asn.allows(&subdomain("mail.corp.test", Some("192.0.2.25"))));
+        assert!(asn.allows_with_ips(&subdomain("mail.corp.test", Some("192.0.2.25")), &found));
+        assert!(!asn.allows_with_ips(&subdomain("evil.test", Some("198.51.100.25")), &found));
+        assert!(!domain.allows_with_ips(&subdomain("evil.test", Some("192.0.2.25")), &found));
+    }
+
+    #[test]
+    fn apply_drops_ips_and_services_without_an_in_scope_name() {
+        let shodan = |name: &str, ip: &str| Subdomain {
+            name: name.to_string(),
+            ip: Some(ip.to_string()),
+            cnames: Vec::new(),
+            wildcard: false,
+            first_seen: None,
+            last_seen: None,
+            issuer: None,
+            certificates: Vec::new(),
+            records: Vec::new(),
+            source: "Shodan".to_string(),
+            sources: Vec::new(),
+        };
+        let service = |ip: &str| crate::types::Service { ip: ip.to_string(), port: 443, ..Default::default() };
+        let result = || ModuleResult {
+            subdomains: vec![shodan("www.corp.com", "192.0.2.10"), shodan("evilcorp.com", "203.0.113.5")],
+            ips: vec!["192.0.2.10".to_string(), "203.0.113.5".to_string()],
+            services: vec![service("192.0.2.10"), service("203.0.113.5")],
+            ..Default::default()
+        };
+
+        // O nome, o IP e o serviço de evilcorp.com contam como descartados
+        let mut domain = result();
+        assert_eq!(scope("corp.com").unwrap().apply(&mut domain), 3);
+        assert_eq!(domain.ips, vec!["192.0.2.10"]);
+        assert_eq!(domain.services.len(), 1);
+
+        // Em blocos de rede vale o IP, mesmo sem nome no escopo
+        let mut network = result();
+        assert_eq!(scope("203.0.113.0/24").unwrap().apply(&mut network), 3);
+        assert_eq!(network.ips, vec!["203.0.113.5"]);
+        assert_eq!(network.services[0].ip, "203.0.113.5");
+
+        // Em ASNs, os IPs trazidos pela busca ficam
+        let mut asn = result();
+        assert_eq!(scope("AS64500").unwrap().apply(&mut asn), 0);
+    }
+
+    #[test]
+    fn include_and_exclude_patterns() {
+        let scope = scope("corp.com")
+            .unwrap()
+            .with_patterns(
+                &["corp-cdn.net".to_string(), "*.corp.io".to_string()],
+                &["*.dev.corp.com".to_string(), "legacy.corp.com".to_string()],
+            )
+            .unwrap();
+
+        assert!(scope.contains("corp-cdn.net"));
+        assert!(scope.contains("img.corp-cdn.net"));
+        assert!(scope.contains("api.corp.io"));
+        assert!(!scope.contains("corp.io"));
+
+        assert!(scope.contains("dev.corp.com"));
+        assert!(!scope.contains("a.dev.corp.com"));
+        assert!(!scope.contains("legacy.corp.com"));
+        assert!(!scope.contains("x.legacy.corp.com"));
+    }
+
+    #[test]
+    fn invalid_or_broad_patterns_are_rejected() {
+        let corp = || scope("corp.com").unwrap();
+
+        assert!(corp().with_patterns(&["*.co.uk".to_string()], &[]).is_err());
+        assert!(corp().with_patterns(&["corp..net".to_string()], &[]).is_err());
+        assert!(corp().with_patterns(&[], &["a.*.corp.com".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,572 @@
+use crate::{
+    jobs::{JobStatus, ScanJob},
+    types::{IpInfo, ModuleCounts, TargetResponse},
+    watch::Watch,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+// Esquema do histórico de escaneamentos
+//
+// Cada item é guardado em sua própria linha, com as colunas usadas em
+// consultas e o registro completo serializado em `data`.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS scans (
+        id TEXT PRIMARY KEY,
+        target TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        finished_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_scans_target ON scans (target, created_at);
+
+    CREATE TABLE IF NOT EXISTS subdomains (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        ip TEXT,
+        source TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_subdomains_scan ON subdomains (scan_id);
+
+    CREATE TABLE IF NOT EXISTS ips (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        ip TEXT NOT NULL,
+        asn INTEGER,
+        data TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_ips_scan ON ips (scan_id);
+
+    CREATE TABLE IF NOT EXISTS services (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        ip TEXT NOT NULL,
+        port INTEGER NOT NULL,
+        service TEXT NOT NULL,
+        source TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_services_scan ON services (scan_id);
+
+    CREATE TABLE IF NOT EXISTS urls (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        url TEXT NOT NULL,
+        source TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_urls_scan ON urls (scan_id);
+
+    CREATE TABLE IF NOT EXISTS dorks (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        query TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_dorks_scan ON dorks (scan_id);
+
+    CREATE TABLE IF NOT EXISTS certificates (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        cert_id TEXT NOT NULL,
+        issuer TEXT,
+        not_after TEXT,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_certificates_scan ON certificates (scan_id);
+
+    CREATE TABLE IF NOT EXISTS findings (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_findings_scan ON findings (scan_id);
+
+    CREATE TABLE IF NOT EXISTS facets (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        facet TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_facets_scan ON facets (scan_id);
+
+    CREATE TABLE IF NOT EXISTS module_reports (
+        scan_id TEXT NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_module_reports_scan ON module_reports (scan_id);
+
+    CREATE TABLE IF NOT EXISTS watches (
+        id TEXT PRIMARY KEY,
+        target TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+";
+
+// Resumo de um escaneamento armazenado
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanSummary {
+    pub id: String,
+    pub target: String,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub counts: ModuleCounts,
+}
+
+// Armazenamento do histórico de escaneamentos em SQLite
+#[derive(Clone)]
+pub struct Storage {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+impl Storage {
+    // Abre (ou cria) o banco de dados no caminho indicado
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let manager = SqliteConnectionManager::file(path.as_ref())
+            .with_init(|conn| conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;"));
+        let pool = Pool::new(manager).context("Falha ao abrir o banco de dados SQLite")?;
+
+        Self::from_pool(pool)
+    }
+
+    // Banco de dados em memória (uma única conexão), útil para testes
+    pub fn open_in_memory() -> Result<Self> {
+        let manager = SqliteConnectionManager::memory()
+            .with_init(|conn| conn.execute_batch("PRAGMA foreign_keys = ON;"));
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(manager)
+            .context("Falha ao abrir o banco de dados SQLite em memória")?;
+
+        Self::from_pool(pool)
+    }
+
+    fn from_pool(pool: Pool<SqliteConnectionManager>) -> Result<Self> {
+        let conn = pool.get()?;
+        conn.execute_batch(SCHEMA)
+            .context("Falha ao criar o esquema do banco de dados")?;
+        migrate(&conn).context("Falha ao atualizar o esquema do banco de dados")?;
+
+        Ok(Self { pool })
+    }
+
+    // Executa uma operação bloqueante do SQLite fora do runtime assíncrono
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get().context("Falha ao obter conexão com o banco de dados")?;
+            f(&mut conn)
+        })
+        .await
+        .context("Tarefa do banco de dados interrompida")?
+    }
+
+    // Persiste um escaneamento finalizado com todos os seus itens
+    //
+    // Só escaneamentos concluídos entram no histórico: um job cancelado tem
+    // resultado parcial, e guardá-lo faria a próxima comparação (`diff`,
+    // monitoramento) apontar como removido tudo o que ele não chegou a coletar.
+    pub async fn save_scan(&self, job: &ScanJob) -> Result<()> {
+        anyhow::ensure!(
+            job.status == JobStatus::Completed,
+            "Escaneamento {} não foi concluído e não é armazenado",
+            job.id
+        );
+        let job = job.clone();
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let result = &job.result;
+
+            tx.execute(
+                "INSERT OR REPLACE INTO scans (id, target, timestamp, created_at, finished_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    job.id,
+                    job.target,
+                    result.timestamp.to_rfc3339(),
+                    job.created_at.to_rfc3339(),
+                    job.finished_at.unwrap_or_else(Utc::now).to_rfc3339(),
+                ],
+            )?;
+
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO subdomains (scan_id, name, ip, source, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for subdomain in &result.subdomains {
+                    stmt.execute(params![
+                        job.id,
+                        subdomain.name,
+                        subdomain.ip,
+                        subdomain.source,
+                        serde_json::to_string(subdomain)?,
+                    ])?;
+                }
+
+                let mut stmt = tx.prepare("INSERT INTO ips (scan_id, ip, asn, data) VALUES (?1, ?2, ?3, ?4)")?;
+                for info in &result.ips {
+                    stmt.execute(params![job.id, info.ip, info.asn, serde_json::to_string(info)?])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO services (scan_id, ip, port, service, source, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for service in &result.services {
+                    stmt.execute(params![
+                        job.id,
+                        service.ip,
+                        service.port,
+                        service.service,
+                        service.source,
+                        serde_json::to_string(service)?,
+                    ])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO urls (scan_id, url, source, data) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for url in &result.urls {
+                    stmt.execute(params![job.id, url.url, url.source, serde_json::to_string(url)?])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO dorks (scan_id, query, data) VALUES (?1, ?2, ?3)",
+                )?;
+                for dork in &result.dorks {
+                    stmt.execute(params![job.id, dork.query, serde_json::to_string(dork)?])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO certificates (scan_id, cert_id, issuer, not_after, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for certificate in &result.certificates {
+                    stmt.execute(params![
+                        job.id,
+                        certificate.id,
+                        certificate.issuer,
+                        certificate.not_after.map(|date| date.to_rfc3339()),
+                        serde_json::to_string(certificate)?,
+                    ])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO findings (scan_id, kind, data) VALUES (?1, ?2, ?3)",
+                )?;
+                for finding in &result.findings {
+                    stmt.execute(params![job.id, finding.kind(), serde_json::to_string(finding)?])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO facets (scan_id, facet, data) VALUES (?1, ?2, ?3)",
+                )?;
+                for facet in &result.facets {
+                    stmt.execute(params![job.id, facet.facet, serde_json::to_string(facet)?])?;
+                }
+
+                let mut stmt = tx.prepare(
+                    "INSERT INTO module_reports (scan_id, position, name, status, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for (position, report) in result.modules.iter().enumerate() {
+                    stmt.execute(params![
+                        job.id,
+                        position as i64,
+                        report.name,
+                        serde_json::to_value(report.status)?.as_str().unwrap_or_default(),
+                        serde_json::to_string(report)?,
+                    ])?;
+                }
+            }
+
+            tx.commit().context("Falha ao gravar escaneamento")?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Lista os escaneamentos armazenados, do mais recente ao mais antigo
+    pub async fn list_scans(&self, target: Option<String>) -> Result<Vec<ScanSummary>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT s.id, s.target, s.created_at, s.finished_at,
+                        (SELECT COUNT(*) FROM subdomains WHERE scan_id = s.id),
+                        (SELECT COUNT(*) FROM ips WHERE scan_id = s.id),
+                        (SELECT COUNT(*) FROM services WHERE scan_id = s.id),
+                        (SELECT COUNT(*) FROM urls WHERE scan_id = s.id),
+                        (SELECT COUNT(*) FROM dorks WHERE scan_id = s.id),
+                        (SELECT COUNT(*) FROM certificates WHERE scan_id = s.id)
+                 FROM scans s
+                 WHERE ?1 IS NULL OR s.target = ?1
+                 ORDER BY s.created_at DESC",
+            )?;
+
+            let rows = stmt.query_map(params![target], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    ModuleCounts {
+                        subdomains: row.get::<_, i64>(4)? as usize,
+                        ips: row.get::<_, i64>(5)? as usize,
+                        services: row.get::<_, i64>(6)? as usize,
+                        urls: row.get::<_, i64>(7)? as usize,
+                        dorks: row.get::<_, i64>(8)? as usize,
+                        certificates: row.get::<_, i64>(9)? as usize,
+                    },
+                ))
+            })?;
+
+            let mut summaries = Vec::new();
+            for row in rows {
+                let (id, target, created_at, finished_at, counts) = row?;
+                summaries.push(ScanSummary {
+                    id,
+                    target,
+                    created_at: parse_datetime(&created_at)?,
+                    finished_at: parse_datetime(&finished_at)?,
+                    counts,
+                });
+            }
+
+            Ok(summaries)
+        })
+        .await
+    }
+
+    // Carrega um escaneamento armazenado completo
+    pub async fn get_scan(&self, id: &str) -> Result<Option<ScanJob>> {
+        let id = id.to_string();
+
+        self.with_conn(move |conn| {
+            let scan = conn
+                .query_row(
+                    "SELECT target, timestamp, created_at, finished_at FROM scans WHERE id = ?1",
+                    params![id],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                            row.get::<_, String>(3)?,
+                        ))
+                    },
+                )
+                .optional()?;
+
+            let Some((target, timestamp, created_at, finished_at)) = scan else {
+                return Ok(None);
+            };
+
+            let mut result = TargetResponse::new(&target);
+            result.timestamp = parse_datetime(&timestamp)?;
+            result.subdomains = load_rows(conn, "SELECT data FROM subdomains WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.services = load_rows(conn, "SELECT data FROM services WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.urls = load_rows(conn, "SELECT data FROM urls WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.dorks = load_rows(conn, "SELECT data FROM dorks WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.certificates = load_rows(conn, "SELECT data FROM certificates WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.findings = load_rows(conn, "SELECT data FROM findings WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.facets = load_rows(conn, "SELECT data FROM facets WHERE scan_id = ?1 ORDER BY rowid", &id)?;
+            result.modules = load_rows(conn, "SELECT data FROM module_reports WHERE scan_id = ?1 ORDER BY position", &id)?;
+
+            // Escaneamentos anteriores aos dados de rede guardam apenas o endereço
+            let mut stmt = conn.prepare("SELECT ip, data FROM ips WHERE scan_id = ?1 ORDER BY rowid")?;
+            let rows = stmt.query_map(params![id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })?;
+            for row in rows {
+                let (ip, data) = row?;
+                result.ips.push(match data {
+                    Some(data) => serde_json::from_str(&data).context("Registro inválido no banco de dados")?,
+                    None => IpInfo::new(ip),
+                });
+            }
+
+            Ok(Some(ScanJob {
+                id,
+                target,
+                status: JobStatus::Completed,
+                created_at: parse_datetime(&created_at)?,
+                finished_at: Some(parse_datetime(&finished_at)?),
+                result,
+            }))
+        })
+        .await
+    }
+
+    // Grava (ou atualiza) um alvo monitorado
+    pub async fn save_watch(&self, watch: &Watch) -> Result<()> {
+        let watch = watch.clone();
+
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO watches (id, target, data) VALUES (?1, ?2, ?3)",
+                params![watch.id, watch.target, serde_json::to_string(&watch)?],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete_watch(&self, id: &str) -> Result<()> {
+        let id = id.to_string();
+
+        self.with_conn(move |conn| {
+            conn.execute("DELETE FROM watches WHERE id = ?1", params![id])?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn list_watches(&self) -> Result<Vec<Watch>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT data FROM watches ORDER BY rowid")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+            let mut watches = Vec::new();
+            for row in rows {
+                watches.push(serde_json::from_str(&row?).context("Registro inválido no banco de dados")?);
+            }
+
+            Ok(watches)
+        })
+        .await
+    }
+}
+
+// Acrescenta as colunas criadas depois da primeira versão do esquema
+fn migrate(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('ips')")?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    if !columns.iter().any(|column| column == "data") {
+        conn.execute_batch("ALTER TABLE ips ADD COLUMN asn INTEGER; ALTER TABLE ips ADD COLUMN data TEXT;")?;
+    }
+
+    Ok(())
+}
+
+// Carrega e desserializa a coluna `data` de uma tabela de itens
+fn load_rows<T: DeserializeOwned>(conn: &Connection, sql: &str, scan_id: &str) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params![scan_id], |row| row.get::<_, String>(0))?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(serde_json::from_str(&row?).context("Registro inválido no banco de dados")?);
+    }
+
+    Ok(items)
+}
+
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Data inválida no banco de dados: {}", value))?
+        .with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn job(id: &str, target: &str, minute: u32, result: TargetResponse) -> ScanJob {
+        let created_at = Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap();
+        ScanJob {
+            id: id.to_string(),
+            target: target.to_string(),
+            status: JobStatus::Completed,
+            created_at,
+            finished_at: Some(created_at + chrono::Duration::seconds(30)),
+            result,
+        }
+    }
+
+    fn full_result() -> TargetResponse {
+        serde_json::from_value(json!({
+            "target": "corp.example",
+            "timestamp": "2024-01-01T12:00:00Z",
+            "subdomains": [
+                { "name": "www.corp.example", "ip": "192.0.2.10", "first_seen": null, "last_seen": null,
+                  "records": [{ "type": "A", "value": "192.0.2.10" }], "source": "Shodan DNS" },
+                { "name": "api.corp.example", "ip": null, "first_seen": null, "last_seen": null, "source": "crt.sh" }
+            ],
+            "ips": ["192.0.2.10", { "ip": "104.16.1.1", "asn": 13335, "kind": "cdn" }],
+            "services": [{ "ip": "192.0.2.10", "port": 443, "service": "nginx", "banner": null, "source": "Shodan" }],
+            "urls": [{ "url": "https://corp.example/", "status_code": 200, "first_seen": null, "last_seen": null, "source": "Wayback" }],
+            "dorks": [{ "query": "site:corp.example", "description": "Páginas indexadas", "results": null }],
+            "certificates": [{ "id": "1", "serial_number": null, "issuer": "C=US, O=Let's Encrypt, CN=R3",
+                               "common_name": "www.corp.example", "names": ["www.corp.example"],
+                               "not_before": null, "not_after": "2024-03-31T00:00:00Z", "logged_at": null, "source": "crt.sh" }],
+            "findings": [{ "kind": "takeover_candidate", "subdomain": "api.corp.example", "cname": "corp.herokuapp.com",
+                           "provider": "Heroku", "evidence": "CNAME sem alvo", "confidence": "medium" }],
+            "facets": [{ "facet": "port", "value": "443", "count": 9, "source": "Shodan" }],
+            "modules": [
+                { "name": "Shodan", "status": "partial", "error": null, "duration_ms": 10,
+                  "counts": { "subdomains": 1, "ips": 1, "services": 1, "urls": 0, "dorks": 0 }, "warnings": ["DNS: 404"] },
+                { "name": "crt.sh", "status": "ok", "error": null, "duration_ms": 5,
+                  "counts": { "subdomains": 1, "ips": 0, "services": 0, "urls": 0, "dorks": 0, "certificates": 1 } }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn scans_round_trip_with_every_item() {
+        let storage = Storage::open_in_memory().unwrap();
+        let saved = job("scan-1", "corp.example", 0, full_result());
+        storage.save_scan(&saved).await.unwrap();
+
+        let loaded = storage.get_scan("scan-1").await.unwrap().expect("escaneamento gravado");
+        assert_eq!(loaded.status, JobStatus::Completed);
+        assert_eq!((loaded.created_at, loaded.finished_at), (saved.created_at, saved.finished_at));
+        // Itens e relatórios voltam na ordem em que foram gravados
+        assert_eq!(serde_json::to_value(&loaded.result).unwrap(), serde_json::to_value(&saved.result).unwrap());
+
+        assert!(storage.get_scan("scan-2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn lists_most_recent_first_with_counts() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_scan(&job("old", "corp.example", 0, full_result())).await.unwrap();
+        storage.save_scan(&job("other", "other.example", 5, TargetResponse::new("other.example"))).await.unwrap();
+        storage.save_scan(&job("new", "corp.example", 10, TargetResponse::new("corp.example"))).await.unwrap();
+
+        let all: Vec<String> = storage.list_scans(None).await.unwrap().into_iter().map(|scan| scan.id).collect();
+        assert_eq!(all, vec!["new", "other", "old"]);
+
+        let corp = storage.list_scans(Some("corp.example".to_string())).await.unwrap();
+        assert_eq!(corp.len(), 2);
+        assert_eq!(corp[1].id, "old");
+        assert_eq!(corp[1].counts, ModuleCounts {
+            subdomains: 2,
+            ips: 2,
+            services: 1,
+            urls: 1,
+            dorks: 1,
+            certificates: 1,
+        });
+        assert_eq!(corp[0].counts, ModuleCounts::default());
+    }
+
+    #[tokio::test]
+    async fn cancelled_scans_are_not_stored() {
+        let storage = Storage::open_in_memory().unwrap();
+        let mut cancelled = job("cancelled", "corp.example", 0, full_result());
+        cancelled.status = JobStatus::Cancelled;
+
+        assert!(storage.save_scan(&cancelled).await.is_err());
+        assert!(storage.list_scans(None).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    config::TakeoverConfig,
+    dns::Resolution,
+    http::HttpClient,
+    types::{Confidence, Finding, TakeoverCandidate, TargetResponse},
+};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Lista de provedores distribuída com o binário
+const BUILTIN_FINGERPRINTS: &str = include_str!("../data/takeover_fingerprints.toml");
+
+#[derive(Debug, Deserialize)]
+struct FingerprintFile {
+    #[serde(default)]
+    provider: Vec<ProviderEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderEntry {
+    name: String,
+    cname: Vec<String>,
+    #[serde(default)]
+    fingerprints: Vec<String>,
+    #[serde(default)]
+    nxdomain: bool,
+}
+
+// Provedor de terceiros com as assinaturas de recurso não reivindicado
+#[derive(Debug, Clone)]
+pub struct Provider {
+    pub name: String,
+    cname: Vec<Regex>,
+    pub fingerprints: Vec<String>,
+    pub nxdomain: bool,
+}
+
+impl Provider {
+    fn matches(&self, name: &str) -> bool {
+        self.cname.iter().any(|pattern| pattern.is_match(name))
+    }
+}
+
+// Interpreta um arquivo de provedores no formato de `data/takeover_fingerprints.toml`
+pub fn parse_providers(content: &str) -> Result<Vec<Provider>> {
+    let file: FingerprintFile = toml::from_str(content).context("Arquivo de assinaturas inválido")?;
+
+    file.provider
+        .into_iter()
+        .map(|entry| {
+            let cname = entry.cname
+                .iter()
+                .map(|pattern| {
+                    Regex::new(pattern).with_context(|| {
+                        format!("Expressão inválida para {}: {}", entry.name, pattern)
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            Ok(Provider {
+                name: entry.name,
+                cname,
+                fingerprints: entry.fingerprints,
+                nxdomain: entry.nxdomain,
+            })
+        })
+        .collect()
+}
+
+// Detecta subdomínios cujo CNAME aponta para serviços de terceiros não reivindicados
+pub struct TakeoverDetector {
+    providers: Vec<Provider>,
+    http: HttpClient,
+    http_probe: bool,
+    timeout: Duration,
+    concurrency: usize,
+}
+
+impl TakeoverDetector {
+    pub fn from_config(config: &TakeoverConfig, http: HttpClient) -> Result<Self> {
+        let providers = match &config.fingerprints_path {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Falha ao ler assinaturas de takeover em {}", path))?;
+                parse_providers(&content)?
+            }
+            None => parse_providers(BUILTIN_FINGERPRINTS)?,
+        };
+
+        tracing::info!("Takeover: {} provedores carregados", providers.len());
+
+        // A sondagem é um único GET: 5xx ou falha de conexão já dizem o suficiente
+        Ok(Self {
+            providers,
+            http: http.without_retries(),
+            http_probe: config.http_probe,
+            timeout: Duration::from_secs(config.timeout_secs),
+            concurrency: config.concurrency.max(1),
+        })
+    }
+
+    pub fn providers(&self) -> &[Provider] {
+        &self.providers
+    }
+
+    // Avalia um subdomínio do alvo a partir da sua resolução DNS
+    pub async fn check(
+        &self,
+        target: &str,
+        subdomain: &str,
+        resolution: &Resolution,
+    ) -> Option<TakeoverCandidate> {
+        let matched = resolution.cnames.iter().find_map(|cname| {
+            self.providers
+                .iter()
+                .find(|provider| provider.matches(cname))
+                .map(|provider| (provider, cname))
+        });
+
+        let candidate = |cname: &str, provider: &str, evidence: String, confidence| TakeoverCandidate {
+            subdomain: subdomain.to_string(),
+            cname: cname.to_string(),
+            provider: provider.to_string(),
+            evidence,
+            confidence,
+        };
+
+        let Some((provider, cname)) = matched else {
+            // CNAME pendente para um domínio fora do alvo, sem provedor conhecido
+            let last = resolution.cnames.last()?;
+            if !resolution.dangling || last == target || last.ends_with(&format!(".{}", target)) {
+                return None;
+            }
+            return Some(candidate(
+                last,
+                "Desconhecido",
+                format!("CNAME {} não resolve (NXDOMAIN)", last),
+                Confidence::Low,
+            ));
+        };
+
+        if resolution.dangling {
+            let confidence = if provider.nxdomain { Confidence::High } else { Confidence::Medium };
+            return Some(candidate(
+                cname,
+                &provider.name,
+                format!("CNAME {} não resolve (NXDOMAIN)", cname),
+                confidence,
+            ));
+        }
+
+        if !self.http_probe || provider.fingerprints.is_empty() {
+            return None;
+        }
+
+        let url = format!("http://{}/", subdomain);
+        let response = match self.http.get(&url, self.timeout).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!("Takeover: falha ao requisitar {}: {:#}", url, e);
+                return None;
+            }
+        };
+
+        let fingerprint = provider.fingerprints.iter().find(|f| response.body.contains(f.as_str()))?;
+
+        Some(candidate(
+            cname,
+            &provider.name,
+            format!("{} retornou {} com \"{}\"", url, response.status.as_u16(), fingerprint),
+            Confidence::High,
+        ))
+    }
+
+    // Adiciona os candidatos encontrados aos achados do alvo
+    pub async fn detect(&self, response: &mut TargetResponse, resolutions: &HashMap<String, Resolution>) {
+        let target = response.target.as_str();
+        let checks: Vec<_> = resolutions
+            .iter()
+            .filter(|(_, resolution)| !resolution.cnames.is_empty())
+            .map(|(name, resolution)| self.check(target, name, resolution))
+            .collect();
+
+        let results: Vec<Option<TakeoverCandidate>> = stream::iter(checks)
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        let candidates: Vec<TakeoverCandidate> = results.into_iter().flatten().collect();
+
+        if !candidates.is_empty() {
+            tracing::warn!(
+                "Takeover: {} candidatos encontrados para {}",
+                candidates.len(),
+                response.target
+            );
+        }
+
+        response.findings.extend(candidates.into_iter().map(Finding::TakeoverCandidate));
+    }
+}
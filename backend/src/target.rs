@@ -0,0 +1,347 @@
+use crate::types::ModuleSkipped;
+use ipnet::IpNet;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+// Limites de tamanho de nomes DNS (RFC 1035)
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+// Alvo de um escaneamento, já validado e normalizado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    // Domínio em ASCII (IDNs convertidos para punycode)
+    Domain(String),
+    Ip(IpAddr),
+    Cidr(IpNet),
+    Asn(u32),
+    Url(url::Url),
+    Email(String),
+}
+
+impl Target {
+    // Descrição do tipo, usada em logs e mensagens
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Target::Domain(_) => "domínio",
+            Target::Ip(_) => "IP",
+            Target::Cidr(_) => "CIDR",
+            Target::Asn(_) => "ASN",
+            Target::Url(_) => "URL",
+            Target::Email(_) => "e-mail",
+        }
+    }
+
+    // Domínio associado ao alvo: o próprio domínio, o host da URL ou o domínio do e-mail
+    pub fn domain(&self) -> Option<&str> {
+        match self {
+            Target::Domain(domain) => Some(domain),
+            Target::Url(url) => match url.host() {
+                Some(url::Host::Domain(domain)) => Some(domain),
+                _ => None,
+            },
+            Target::Email(email) => email.split_once('@').map(|(_, domain)| domain),
+            _ => None,
+        }
+    }
+
+    // IP associado ao alvo: o próprio IP ou o host da URL
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Target::Ip(ip) => Some(*ip),
+            Target::Url(url) => match url.host()? {
+                url::Host::Ipv4(ip) => Some(IpAddr::V4(ip)),
+                url::Host::Ipv6(ip) => Some(IpAddr::V6(ip)),
+                url::Host::Domain(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    // Recusa blocos CIDR maiores que `limit`, sem expandir os endereços
+    pub fn check_size(&self, limit: usize) -> Result<(), String> {
+        if let Target::Cidr(net) = self {
+            let bits = u32::from(net.max_prefix_len() - net.prefix_len());
+            let size = 1u128.checked_shl(bits).unwrap_or(u128::MAX);
+            if size > limit as u128 {
+                return Err(format!("{} tem {} endereços, acima do limite de {}", net, size, limit));
+            }
+        }
+        Ok(())
+    }
+
+    // Endereços de um IP ou bloco CIDR, recusando blocos maiores que `limit`
+    pub fn hosts(&self, limit: usize) -> Result<Vec<IpAddr>, String> {
+        self.check_size(limit)?;
+        match self {
+            Target::Ip(ip) => Ok(vec![*ip]),
+            Target::Cidr(net) => Ok(net.hosts().collect()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    // Indica se um endereço pertence ao alvo de rede
+    //
+    // ASNs não podem ser conferidos localmente e nunca cobrem um endereço por
+    // si só; o escopo aceita apenas os IPs devolvidos pela busca do próprio ASN
+    // (ver `Scope::allows_with_ips`).
+    pub fn covers(&self, ip: IpAddr) -> bool {
+        match self {
+            Target::Cidr(net) => net.contains(&ip),
+            Target::Asn(_) => false,
+            _ => self.ip() == Some(ip),
+        }
+    }
+
+    // Chave do alvo no histórico, nos jobs e nos monitoramentos
+    //
+    // Os módulos só usam o host de uma URL, então `https://corp.example/a` e
+    // `/b` são o mesmo escaneamento e precisam ser comparados entre si.
+    pub fn history_key(&self) -> String {
+        match self {
+            Target::Url(_) => self.domain()
+                .map(str::to_string)
+                .or_else(|| self.ip().map(|ip| ip.to_string()))
+                .unwrap_or_else(|| self.to_string()),
+            _ => self.to_string(),
+        }
+    }
+
+    // Domínio do alvo para módulos que só consultam nomes; os demais tipos são ignorados
+    pub fn require_domain(&self, module: &str) -> anyhow::Result<&str> {
+        self.domain().ok_or_else(|| {
+            ModuleSkipped(format!("{} não consulta alvos do tipo {}", module, self.kind())).into()
+        })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Domain(domain) => write!(f, "{}", domain),
+            Target::Ip(ip) => write!(f, "{}", ip),
+            Target::Cidr(net) => write!(f, "{}", net),
+            Target::Asn(asn) => write!(f, "AS{}", asn),
+            Target::Url(url) => write!(f, "{}", url),
+            Target::Email(email) => write!(f, "{}", email),
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    // Interpreta a entrada do usuário, retornando o motivo quando inválida
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err("Alvo vazio fornecido".to_string());
+        }
+
+        if raw.contains("://") {
+            return parse_url(raw);
+        }
+
+        if let Some(asn) = raw.get(..2).filter(|prefix| prefix.eq_ignore_ascii_case("as")).map(|_| &raw[2..]) {
+            if !asn.is_empty() && asn.chars().all(|c| c.is_ascii_digit()) {
+                return asn
+                    .parse()
+                    .map(Target::Asn)
+                    .map_err(|_| format!("ASN fora do intervalo: {}", raw));
+            }
+        }
+
+        if let Some((local, domain)) = raw.split_once('@') {
+            if local.is_empty() || local.chars().any(|c| c.is_whitespace() || c == '@') {
+                return Err(format!("E-mail inválido: {}", raw));
+            }
+            let domain = parse_domain(domain)?;
+            return Ok(Target::Email(format!("{}@{}", local.to_lowercase(), domain)));
+        }
+
+        if let Ok(net) = raw.parse::<IpNet>() {
+            return Ok(Target::Cidr(net.trunc()));
+        }
+
+        let unbracketed = raw.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = unbracketed.parse::<IpAddr>() {
+            return Ok(Target::Ip(ip));
+        }
+
+        // Host com porta ou caminho, mas sem esquema (ex.: corp.com/login)
+        if raw.contains(['/', ':', '?', '#']) {
+            // IP seguido de barra só pode ser um CIDR, e este já falhou acima
+            if raw.split_once('/').is_some_and(|(ip, _)| ip.parse::<IpAddr>().is_ok()) {
+                return Err(format!("CIDR inválido: {}", raw));
+            }
+            return parse_url(&format!("http://{}", raw));
+        }
+
+        parse_domain(raw).map(Target::Domain)
+    }
+}
+
+// URL http(s), sem credenciais nem fragmento
+fn parse_url(raw: &str) -> Result<Target, String> {
+    let mut url = url::Url::parse(raw).map_err(|e| format!("URL inválida: {} ({})", raw, e))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Esquema não suportado: {} (use http ou https)", url.scheme()));
+    }
+
+    match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            validate_domain(domain)?;
+        }
+        Some(_) => {}
+        None => return Err(format!("URL sem host: {}", raw)),
+    }
+
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_fragment(None);
+
+    Ok(Target::Url(url))
+}
+
+// Converte o domínio para ASCII (IDNA) e valida sua sintaxe
+fn parse_domain(raw: &str) -> Result<String, String> {
+    let name = raw.trim().trim_end_matches('.');
+    if name.is_empty() {
+        return Err("Domínio vazio".to_string());
+    }
+    if name.contains('*') {
+        return Err(format!("Curingas não são aceitos como alvo: {}", raw.trim()));
+    }
+
+    match url::Host::parse(name) {
+        Ok(url::Host::Domain(domain)) => {
+            validate_domain(&domain)?;
+            Ok(domain)
+        }
+        // Formas numéricas abreviadas (ex.: 10.1) que não são IPs completos
+        Ok(_) => Err(format!("Endereço IP inválido: {}", raw.trim())),
+        Err(e) => Err(format!("Domínio inválido: {} ({})", raw.trim(), e)),
+    }
+}
+
+fn validate_domain(domain: &str) -> Result<(), String> {
+    if domain.len() > MAX_DOMAIN_LEN {
+        return Err(format!("Domínio excede {} caracteres", MAX_DOMAIN_LEN));
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        return Err(format!("Domínio precisa de pelo menos dois rótulos: {}", domain));
+    }
+
+    for label in &labels {
+        if label.is_empty() {
+            return Err(format!("Domínio com rótulo vazio: {}", domain));
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(format!("Rótulo com mais de {} caracteres: {}", MAX_LABEL_LEN, label));
+        }
+        if let Some(c) = label.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+            return Err(format!("Caractere inválido '{}' em {}", c, domain));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!("Rótulo não pode começar ou terminar com hífen: {}", label));
+        }
+    }
+
+    if labels.last().is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit())) {
+        return Err(format!("TLD numérico em {}", domain));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Target {
+        raw.parse().unwrap_or_else(|e| panic!("{}: {}", raw, e))
+    }
+
+    #[test]
+    fn parses_each_kind() {
+        assert_eq!(parse(" Corp.Example. "), Target::Domain("corp.example".to_string()));
+        assert_eq!(parse("192.0.2.10"), Target::Ip("192.0.2.10".parse().unwrap()));
+        assert_eq!(parse("[2001:db8::1]"), Target::Ip("2001:db8::1".parse().unwrap()));
+        assert_eq!(parse("192.0.2.77/24"), Target::Cidr("192.0.2.0/24".parse().unwrap()));
+        assert_eq!(parse("as15169"), Target::Asn(15169));
+        assert_eq!(parse("Admin@Corp.Example"), Target::Email("admin@corp.example".to_string()));
+
+        let Target::Url(url) = parse("https://user:pw@www.corp.example/login?next=/#top") else {
+            panic!("URL esperada");
+        };
+        assert_eq!(url.as_str(), "https://www.corp.example/login?next=/");
+    }
+
+    #[test]
+    fn converts_idn_to_punycode() {
+        assert_eq!(parse("münchen.de"), Target::Domain("xn--mnchen-3ya.de".to_string()));
+        assert_eq!(parse("https://BÜCHER.example/").domain(), Some("xn--bcher-kva.example"));
+    }
+
+    #[test]
+    fn urls_without_scheme_keep_only_the_host_for_modules() {
+        let target = parse("www.corp.example/login");
+        assert_eq!(target.kind(), "URL");
+        assert_eq!(target.domain(), Some("www.corp.example"));
+
+        assert_eq!(parse("corp.example:8443").domain(), Some("corp.example"));
+        assert_eq!(parse("http://192.0.2.10:8080/").ip(), Some("192.0.2.10".parse().unwrap()));
+    }
+
+    #[test]
+    fn urls_are_keyed_by_host() {
+        assert_eq!(parse("https://corp.example/a").history_key(), "corp.example");
+        assert_eq!(parse("https://Corp.Example/b?x=1").history_key(), "corp.example");
+        assert_eq!(parse("http://192.0.2.10:8080/admin").history_key(), "192.0.2.10");
+        assert_eq!(parse("AS64500").history_key(), "AS64500");
+    }
+
+    #[test]
+    fn network_targets_expand_within_the_limit() {
+        let hosts = parse("192.0.2.0/30").hosts(256).unwrap();
+        assert_eq!(hosts, vec!["192.0.2.1".parse::<IpAddr>().unwrap(), "192.0.2.2".parse().unwrap()]);
+        assert_eq!(parse("192.0.2.10").hosts(1).unwrap().len(), 1);
+        assert!(parse("corp.example").hosts(256).unwrap().is_empty());
+
+        let reason = parse("10.0.0.0/16").hosts(256).unwrap_err();
+        assert_eq!(reason, "10.0.0.0/16 tem 65536 endereços, acima do limite de 256");
+        assert!(parse("2001:db8::/32").hosts(256).is_err());
+
+        // A checagem de tamanho não depende de expandir o bloco
+        assert!(parse("10.0.0.0/8").check_size(1 << 24).is_ok());
+        assert!(parse("::/0").check_size(usize::MAX).is_err());
+        assert!(parse("AS64500").check_size(0).is_ok());
+
+        let net = parse("192.0.2.0/24");
+        assert!(net.covers("192.0.2.200".parse().unwrap()));
+        assert!(!net.covers("198.51.100.1".parse().unwrap()));
+        assert!(!parse("AS64500").covers("192.0.2.200".parse().unwrap()));
+    }
+
+    #[test]
+    fn rejects_invalid_input_with_a_reason() {
+        let reason = |raw: &str| raw.parse::<Target>().unwrap_err();
+
+        assert_eq!(reason("  "), "Alvo vazio fornecido");
+        assert!(reason("localhost").contains("dois rótulos"), "{}", reason("localhost"));
+        assert!(reason("*.corp.example").contains("Curingas"));
+        assert!(reason("corp..example").contains("rótulo vazio"));
+        assert!(reason("-corp.example").contains("hífen"));
+        assert!(reason("corp$.example").contains("Caractere inválido '$'"));
+        assert!(reason(&format!("{}.example", "a".repeat(64))).contains("63"));
+        assert!(reason("ftp://corp.example/").contains("Esquema não suportado"));
+        assert!(reason("192.0.2.0/33").contains("CIDR inválido"));
+        assert!(reason("10.1").contains("IP inválido"));
+        assert!(reason("AS99999999999").contains("ASN fora do intervalo"));
+        assert!(reason("@corp.example").contains("E-mail inválido"));
+    }
+}
@@ -1,20 +1,64 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
 use axum::{
     response::{IntoResponse, Response},
     http::StatusCode,
     Json,
 };
+use std::collections::{btree_map::Entry, BTreeMap};
 use std::fmt;
+use crate::{
+    http::HttpError,
+    jobs::{JobManager, ScanJob},
+    registry::ModuleRegistry,
+    watch::Watchlist,
+};
+#[cfg(feature = "sqlite")]
+use crate::storage::Storage;
 
 // Estado da aplicação
 pub struct AppState {
-    // Para uso futuro (cache, configurações, etc.)
+    pub registry: ModuleRegistry,
+    pub jobs: JobManager,
+    pub watchlist: Watchlist,
+    #[cfg(feature = "sqlite")]
+    pub storage: Option<Storage>,
 }
 
 impl AppState {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(registry: ModuleRegistry) -> Self {
+        Self {
+            registry,
+            jobs: JobManager::new(),
+            watchlist: Watchlist::default(),
+            #[cfg(feature = "sqlite")]
+            storage: None,
+        }
+    }
+
+    pub fn with_watchlist(mut self, watchlist: Watchlist) -> Self {
+        self.watchlist = watchlist;
+        self
+    }
+
+    // Habilita a persistência do histórico de escaneamentos
+    #[cfg(feature = "sqlite")]
+    pub fn with_storage(mut self, storage: Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    // Persiste um escaneamento finalizado, se houver armazenamento configurado
+    pub async fn persist(&self, job: &ScanJob) {
+        #[cfg(feature = "sqlite")]
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.save_scan(job).await {
+                tracing::error!("Falha ao persistir escaneamento {}: {:#}", job.id, e);
+            }
+        }
+
+        #[cfg(not(feature = "sqlite"))]
+        let _ = job;
     }
 }
 
@@ -22,42 +66,433 @@ impl AppState {
 #[derive(Debug, Deserialize)]
 pub struct TargetRequest {
     pub target: String,
+    // Padrões de escopo adicionais (ver `scope::Scope::with_patterns`)
+    #[serde(default, deserialize_with = "patterns")]
+    pub include: Vec<String>,
+    #[serde(default, deserialize_with = "patterns")]
+    pub exclude: Vec<String>,
+}
+
+// Aceita uma lista JSON ou, na query string, uma string separada por vírgulas
+pub fn patterns<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Patterns {
+        List(Vec<String>),
+        Joined(String),
+    }
+
+    let patterns = match Patterns::deserialize(deserializer)? {
+        Patterns::List(list) => list,
+        Patterns::Joined(joined) => joined.split(',').map(str::to_string).collect(),
+    };
+
+    Ok(patterns
+        .into_iter()
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect())
 }
 
 // Resposta completa de escaneamento
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TargetResponse {
     pub target: String,
     pub timestamp: DateTime<Utc>,
     pub subdomains: Vec<Subdomain>,
-    pub ips: Vec<String>,
+    #[serde(deserialize_with = "ip_records")]
+    pub ips: Vec<IpInfo>,
     pub services: Vec<Service>,
     pub urls: Vec<Url>,
     pub dorks: Vec<Dork>,
+    #[serde(default)]
+    pub certificates: Vec<Certificate>,
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+    // Contagens agregadas da exposição (ex.: modo de facetas do Shodan)
+    #[serde(default)]
+    pub facets: Vec<FacetCount>,
+    pub modules: Vec<ModuleReport>,
+}
+
+impl TargetResponse {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            timestamp: Utc::now(),
+            subdomains: Vec::new(),
+            ips: Vec::new(),
+            services: Vec::new(),
+            urls: Vec::new(),
+            dorks: Vec::new(),
+            certificates: Vec::new(),
+            findings: Vec::new(),
+            facets: Vec::new(),
+            modules: Vec::new(),
+        }
+    }
+
+    // Adiciona o resultado de um módulo à resposta
+    pub fn merge(&mut self, result: ModuleResult) {
+        self.subdomains.extend(result.subdomains);
+        self.ips.extend(result.ips.into_iter().map(IpInfo::new));
+        self.services.extend(result.services);
+        self.urls.extend(result.urls);
+        self.dorks.extend(result.dorks);
+        self.certificates.extend(result.certificates);
+        self.facets.extend(result.facets);
+    }
+
+    // Registra a execução de um módulo e seus dados, se houver
+    pub fn record(&mut self, report: ModuleReport, data: Option<ModuleResult>) {
+        if let Some(data) = data {
+            self.merge(data);
+        }
+        self.modules.push(report);
+    }
+
+    // Remove duplicatas após combinar os módulos
+    //
+    // Subdomínios com o mesmo nome vindos de fontes diferentes viram um só.
+    pub fn dedup(&mut self) {
+        let mut merged: BTreeMap<String, Subdomain> = BTreeMap::new();
+        for subdomain in self.subdomains.drain(..) {
+            match merged.entry(subdomain.name.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(subdomain);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().absorb(subdomain),
+            }
+        }
+        self.subdomains = merged.into_values().collect();
+
+        // Mantém a ocorrência com dados de rede, se alguma tiver
+        self.ips.sort_by(|a, b| a.ip.cmp(&b.ip).then(b.asn.is_some().cmp(&a.asn.is_some())));
+        self.ips.dedup_by(|a, b| a.ip == b.ip);
+
+        // O mesmo certificado pode vir de consultas diferentes
+        self.certificates.sort_by(|a, b| Certificate::order_key(&a.id).cmp(&Certificate::order_key(&b.id)));
+        self.certificates.dedup_by(|a, b| a.id == b.id);
+
+        self.findings.sort_unstable();
+        self.findings.dedup();
+    }
+}
+
+// Endereço IP com os dados da rede que o anuncia
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct IpInfo {
+    pub ip: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    // Organização dona do ASN (ex.: `CLOUDFLARENET - Cloudflare, Inc., US`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_org: Option<String>,
+    // Prefixo BGP que contém o endereço
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    // Código ISO do país de registro
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    // Provedor conhecido de CDN ou hospedagem; vazio indica infraestrutura própria
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<NetworkKind>,
+}
+
+impl IpInfo {
+    pub fn new(ip: impl Into<String>) -> Self {
+        Self {
+            ip: ip.into(),
+            asn: None,
+            as_org: None,
+            network: None,
+            country: None,
+            provider: None,
+            kind: None,
+        }
+    }
+
+    // Endereço de borda de CDN, e não da infraestrutura do alvo
+    pub fn is_edge(&self) -> bool {
+        self.kind == Some(NetworkKind::Cdn)
+    }
+}
+
+// Classificação da rede de um endereço
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkKind {
+    Cdn,
+    Hosting,
+}
+
+// Aceita os registros completos ou, em escaneamentos antigos, apenas os endereços
+fn ip_records<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<IpInfo>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Record {
+        Info(IpInfo),
+        Bare(String),
+    }
+
+    Ok(Vec::<Record>::deserialize(deserializer)?
+        .into_iter()
+        .map(|record| match record {
+            Record::Info(info) => info,
+            Record::Bare(ip) => IpInfo::new(ip),
+        })
+        .collect())
 }
 
 // Estrutura de subdomínio
-#[derive(Debug, Serialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Subdomain {
     pub name: String,
     pub ip: Option<String>,
+    // Cadeia de CNAMEs obtida na resolução DNS
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cnames: Vec<String>,
+    // Visto como `*.nome` em algum certificado
+    #[serde(default)]
+    pub wildcard: bool,
     pub first_seen: Option<DateTime<Utc>>,
     pub last_seen: Option<DateTime<Utc>>,
+    // Emissor do certificado em que o nome foi visto (fontes de CT)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    // Ids dos certificados (`TargetResponse.certificates`) que citam o nome
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub certificates: Vec<String>,
+    // Registros DNS informados pela fonte (ex.: Shodan DNS)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub records: Vec<DnsRecord>,
+    // Uma única fonte; para nomes vistos por várias, a primeira de `sources`
+    pub source: String,
+    // Todas as fontes, em ordem alfabética, quando o nome veio de mais de uma
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+}
+
+// Registro DNS de um nome, como visto pela fonte
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct DnsRecord {
+    // Tipo do registro (A, AAAA, CNAME, MX, TXT...)
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+}
+
+impl Subdomain {
+    // Incorpora os dados de outra ocorrência do mesmo nome
+    pub fn absorb(&mut self, other: Subdomain) {
+        if self.ip.is_none() {
+            self.ip = other.ip;
+        }
+        if self.cnames.is_empty() {
+            self.cnames = other.cnames;
+        }
+        if self.issuer.is_none() {
+            self.issuer = other.issuer;
+        }
+        self.wildcard |= other.wildcard;
+
+        self.first_seen = match (self.first_seen, other.first_seen) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_seen = self.last_seen.max(other.last_seen);
+
+        self.certificates.extend(other.certificates);
+        self.certificates.sort_unstable_by(|a, b| Certificate::order_key(a).cmp(&Certificate::order_key(b)));
+        self.certificates.dedup();
+
+        self.records.extend(other.records);
+        self.records.sort_unstable();
+        self.records.dedup();
+
+        // A ordem de chegada varia entre escaneamentos; a alfabética não
+        let mut sources: Vec<String> = std::mem::take(&mut self.sources)
+            .into_iter()
+            .chain(other.sources)
+            .chain([std::mem::take(&mut self.source), other.source])
+            .filter(|source| !source.is_empty())
+            .collect();
+        sources.sort_unstable();
+        sources.dedup();
+        self.source = sources.first().cloned().unwrap_or_default();
+        if sources.len() > 1 {
+            self.sources = sources;
+        }
+    }
+
+    // Fontes que citaram o nome
+    pub fn sources(&self) -> Vec<&str> {
+        if self.sources.is_empty() {
+            vec![self.source.as_str()]
+        } else {
+            self.sources.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+// Certificado encontrado em logs de Certificate Transparency
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Certificate {
+    // Id do certificado na fonte: o id do crt.sh ou, nas demais fontes, o id
+    // prefixado pelo nome da fonte (ex.: `certspotter:1234`)
+    pub id: String,
+    pub serial_number: Option<String>,
+    pub issuer: Option<String>,
+    pub common_name: Option<String>,
+    // Nomes (SANs) cobertos pelo certificado
+    pub names: Vec<String>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    // Momento em que o certificado entrou no log
+    pub logged_at: Option<DateTime<Utc>>,
     pub source: String,
 }
 
+impl Certificate {
+    // Chave de ordenação de um id: fonte e número, para que `10` venha depois de `9`
+    pub fn order_key(id: &str) -> (&str, u64, &str) {
+        let (prefix, number) = id.rsplit_once(':').unwrap_or(("", id));
+        (prefix, number.parse().unwrap_or(u64::MAX), id)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.not_after.is_some_and(|not_after| not_after < now)
+    }
+
+    // Ainda válido, mas expira dentro do prazo indicado
+    pub fn expires_within(&self, now: DateTime<Utc>, within: chrono::Duration) -> bool {
+        self.not_after
+            .is_some_and(|not_after| not_after >= now && not_after - now <= within)
+    }
+}
+
 // Estrutura de serviço
-#[derive(Debug, Serialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Service {
     pub ip: String,
     pub port: u16,
+    // Protocolo de transporte (`tcp` ou `udp`); registros sem o campo valem como `tcp`
+    #[serde(default = "default_transport")]
+    pub transport: String,
     pub service: String,
     pub banner: Option<String>,
     pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsInfo>,
+    // Identificadores CPE 2.3 do produto
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpes: Vec<String>,
+    // Vulnerabilidades conhecidas, da maior nota CVSS para a menor
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cves: Vec<Vulnerability>,
+    // Última vez em que a fonte observou o serviço
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl Default for Service {
+    fn default() -> Self {
+        Self {
+            ip: String::new(),
+            port: 0,
+            transport: default_transport(),
+            service: String::new(),
+            banner: None,
+            source: String::new(),
+            http: None,
+            tls: None,
+            cpes: Vec::new(),
+            cves: Vec::new(),
+            last_seen: None,
+        }
+    }
+}
+
+fn default_transport() -> String {
+    "tcp".to_string()
+}
+
+// Resposta HTTP de um serviço web
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct HttpInfo {
+    pub status: Option<u16>,
+    pub title: Option<String>,
+    pub server: Option<String>,
+    // Hash do favicon no formato do Shodan (`http.favicon.hash:`)
+    pub favicon_hash: Option<i64>,
+    // Destinos dos redirecionamentos seguidos, em ordem
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<String>,
+}
+
+// Certificado e parâmetros TLS de um serviço
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct TlsInfo {
+    // CN do titular do certificado
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub not_after: Option<DateTime<Utc>>,
+    // Versões do protocolo aceitas pelo servidor
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    pub cipher: Option<String>,
+    // SHA-256 do certificado
+    pub fingerprint: Option<String>,
+    pub jarm: Option<String>,
+}
+
+// Vulnerabilidade associada a um serviço
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vulnerability {
+    // Identificador CVE (ex.: CVE-2021-23017)
+    pub id: String,
+    pub cvss: Option<f64>,
+    // Confirmada pela fonte, e não apenas inferida pela versão do produto
+    #[serde(default)]
+    pub verified: bool,
+    pub summary: Option<String>,
+}
+
+// A igualdade segue a ordem total (`total_cmp`) das notas CVSS, para que
+// `Eq`, `Ord` e a ordenação dos serviços concordem entre si
+impl PartialEq for Vulnerability {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Vulnerability {}
+
+impl Ord for Vulnerability {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id
+            .cmp(&other.id)
+            .then_with(|| match (self.cvss, other.cvss) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+            .then_with(|| self.verified.cmp(&other.verified))
+            .then_with(|| self.summary.cmp(&other.summary))
+    }
+}
+
+impl PartialOrd for Vulnerability {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 // Estrutura de URL
-#[derive(Debug, Serialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Url {
     pub url: String,
     pub status_code: Option<u16>,
@@ -67,13 +502,68 @@ pub struct Url {
 }
 
 // Estrutura de Dork
-#[derive(Debug, Serialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Dork {
     pub query: String,
     pub description: String,
     pub results: Option<usize>,
 }
 
+// Achado de segurança derivado dos dados coletados
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    TakeoverCandidate(TakeoverCandidate),
+}
+
+impl Finding {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Finding::TakeoverCandidate(_) => "takeover_candidate",
+        }
+    }
+}
+
+// Subdomínio apontando para um serviço de terceiros possivelmente não reivindicado
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct TakeoverCandidate {
+    pub subdomain: String,
+    // CNAME da cadeia que identificou o provedor
+    pub cname: String,
+    pub provider: String,
+    pub evidence: String,
+    pub confidence: Confidence,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+// Quantidade de hosts com um valor de atributo (ex.: porta 443, país BR)
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct FacetCount {
+    pub facet: String,
+    pub value: String,
+    pub count: u64,
+    pub source: String,
+}
+
+// Consumo de créditos pagos de uma API durante o escaneamento
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CreditUsage {
+    pub used: u32,
+    // Saldo informado pela API antes do escaneamento
+    pub remaining: Option<u32>,
+    // Limite configurado por escaneamento
+    pub budget: Option<u32>,
+    // Requisições deixadas de fazer por falta de créditos
+    pub skipped: u32,
+}
+
 // Resultado genérico de módulo OSINT
 #[derive(Debug, Default, Serialize)]
 pub struct ModuleResult {
@@ -82,14 +572,88 @@ pub struct ModuleResult {
     pub services: Vec<Service>,
     pub urls: Vec<Url>,
     pub dorks: Vec<Dork>,
+    pub certificates: Vec<Certificate>,
+    pub facets: Vec<FacetCount>,
+    // Créditos gastos, para módulos de APIs pagas
+    pub usage: Option<CreditUsage>,
+    // Consultas que falharam sem impedir o módulo de trazer dados
+    pub warnings: Vec<String>,
+}
+
+impl ModuleResult {
+    pub fn counts(&self) -> ModuleCounts {
+        ModuleCounts {
+            subdomains: self.subdomains.len(),
+            ips: self.ips.len(),
+            services: self.services.len(),
+            urls: self.urls.len(),
+            dorks: self.dorks.len(),
+            certificates: self.certificates.len(),
+        }
+    }
+}
+
+// Situação final da execução de um módulo
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleStatus {
+    Ok,
+    // Trouxe dados, mas alguma consulta falhou; vale executar de novo
+    Partial,
+    Skipped,
+    Failed,
+    TimedOut,
+}
+
+// Quantidade de itens produzidos por um módulo
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleCounts {
+    pub subdomains: usize,
+    pub ips: usize,
+    pub services: usize,
+    pub urls: usize,
+    pub dorks: usize,
+    #[serde(default)]
+    pub certificates: usize,
+}
+
+// Relatório de execução de um módulo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModuleReport {
+    pub name: String,
+    pub status: ModuleStatus,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub counts: ModuleCounts,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<CreditUsage>,
+    // Falha HTTP que encerrou o módulo (status, host, tentativas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_error: Option<HttpError>,
+    // Falhas parciais de um módulo que ainda trouxe dados
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Erro retornado por um módulo que decidiu não executar (ex.: sem API key)
+#[derive(Debug)]
+pub struct ModuleSkipped(pub String);
+
+impl fmt::Display for ModuleSkipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Módulo ignorado: {}", self.0)
+    }
 }
 
+impl std::error::Error for ModuleSkipped {}
+
 // Erros da aplicação
 #[derive(Debug)]
 pub enum AppError {
     InvalidInput(String),
     NetworkError(String),
     ModuleError(String),
+    NotFound(String),
     InternalError(String),
 }
 
@@ -99,6 +663,7 @@ impl fmt::Display for AppError {
             AppError::InvalidInput(msg) => write!(f, "Entrada inválida: {}", msg),
             AppError::NetworkError(msg) => write!(f, "Erro de rede: {}", msg),
             AppError::ModuleError(msg) => write!(f, "Erro de módulo: {}", msg),
+            AppError::NotFound(msg) => write!(f, "Não encontrado: {}", msg),
             AppError::InternalError(msg) => write!(f, "Erro interno: {}", msg),
         }
     }
@@ -112,6 +677,7 @@ impl IntoResponse for AppError {
             AppError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::NetworkError(msg) => (StatusCode::BAD_GATEWAY, msg),
             AppError::ModuleError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
@@ -0,0 +1,399 @@
+use crate::{
+    diff::diff_scans,
+    jobs::ScanJob,
+    scope::Scope,
+    types::{patterns, AppError, AppState, Service},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+// Frequência com que o agendador verifica alvos pendentes
+const SCHEDULER_TICK: Duration = Duration::from_secs(15);
+
+// Menor intervalo aceito entre dois escaneamentos do mesmo alvo
+const MIN_INTERVAL_SECS: u64 = 60;
+
+// Maior intervalo aceito (365 dias)
+const MAX_INTERVAL_SECS: u64 = 365 * 86400;
+
+// Request para monitorar um alvo
+#[derive(Debug, Deserialize)]
+pub struct WatchRequest {
+    pub target: String,
+    pub interval: String,
+    pub webhook: Option<String>,
+    #[serde(default, deserialize_with = "patterns")]
+    pub include: Vec<String>,
+    #[serde(default, deserialize_with = "patterns")]
+    pub exclude: Vec<String>,
+}
+
+// Alvo monitorado continuamente
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Watch {
+    pub id: String,
+    pub target: String,
+    pub interval: String,
+    pub interval_secs: u64,
+    pub webhook: Option<String>,
+    // Padrões de escopo aplicados a cada escaneamento
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: DateTime<Utc>,
+    pub last_scan_id: Option<String>,
+}
+
+impl Watch {
+    // Escopo usado nos escaneamentos do alvo
+    pub fn scope(&self) -> anyhow::Result<Scope> {
+        let target = self.target.parse().map_err(anyhow::Error::msg)?;
+        Scope::new(target)?.with_patterns(&self.include, &self.exclude)
+    }
+}
+
+// Alerta enviado ao webhook quando a superfície de ataque cresce
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchAlert {
+    pub event: String,
+    pub watch_id: String,
+    pub target: String,
+    pub from: String,
+    pub to: String,
+    pub timestamp: DateTime<Utc>,
+    pub new_subdomains: Vec<String>,
+    pub new_services: Vec<Service>,
+}
+
+struct WatchEntry {
+    watch: Watch,
+    last: Option<ScanJob>,
+    running: bool,
+}
+
+// Lista de alvos monitorados
+pub struct Watchlist {
+    watches: Mutex<HashMap<String, WatchEntry>>,
+    default_webhook: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Default for Watchlist {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Watchlist {
+    // `default_webhook` é usado pelos alvos registrados sem webhook próprio
+    pub fn new(default_webhook: Option<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            watches: Mutex::new(HashMap::new()),
+            default_webhook,
+            client,
+        }
+    }
+
+    // Registra um novo alvo, validando intervalo e webhook
+    pub fn add(&self, target: &str, request: &WatchRequest) -> Result<Watch, AppError> {
+        let interval_secs = parse_interval(&request.interval)
+            .map_err(AppError::InvalidInput)?;
+
+        let webhook = request.webhook
+            .as_deref()
+            .map(str::trim)
+            .filter(|webhook| !webhook.is_empty())
+            .map(|webhook| {
+                url::Url::parse(webhook)
+                    .map(|_| webhook.to_string())
+                    .map_err(|e| AppError::InvalidInput(format!("Webhook inválido: {}", e)))
+            })
+            .transpose()?;
+
+        let now = Utc::now();
+        let mut watch = Watch {
+            id: Uuid::new_v4().to_string(),
+            target: target.to_string(),
+            interval: request.interval.trim().to_string(),
+            interval_secs,
+            webhook,
+            include: request.include.clone(),
+            exclude: request.exclude.clone(),
+            created_at: now,
+            last_run: None,
+            next_run: now,
+            last_scan_id: None,
+        };
+
+        let scope = watch.scope().map_err(|e| AppError::InvalidInput(e.to_string()))?;
+        watch.target = scope.target().history_key();
+
+        self.restore(watch.clone());
+
+        Ok(watch)
+    }
+
+    // Recoloca um alvo na lista (ex.: carregado do histórico)
+    pub fn restore(&self, watch: Watch) {
+        self.watches.lock().unwrap().insert(watch.id.clone(), WatchEntry {
+            watch,
+            last: None,
+            running: false,
+        });
+    }
+
+    pub fn list(&self) -> Vec<Watch> {
+        let mut watches: Vec<Watch> = self.watches
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.watch.clone())
+            .collect();
+        watches.sort_by_key(|watch| watch.created_at);
+        watches
+    }
+
+    pub fn remove(&self, id: &str) -> Option<Watch> {
+        self.watches.lock().unwrap().remove(id).map(|entry| entry.watch)
+    }
+
+    // Marca como em execução e retorna os alvos cujo horário chegou
+    fn take_due(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut watches = self.watches.lock().unwrap();
+        watches
+            .values_mut()
+            .filter(|entry| !entry.running && entry.watch.next_run <= now)
+            .map(|entry| {
+                entry.running = true;
+                entry.watch.id.clone()
+            })
+            .collect()
+    }
+
+    // Inicia uma execução, retornando o alvo e o último resultado conhecido
+    fn begin(&self, id: &str) -> Option<(Watch, Option<ScanJob>)> {
+        let mut watches = self.watches.lock().unwrap();
+        let entry = watches.get_mut(id)?;
+        entry.running = true;
+        Some((entry.watch.clone(), entry.last.clone()))
+    }
+
+    // Registra o resultado de uma execução e agenda a próxima
+    fn complete(&self, id: &str, job: Option<ScanJob>) -> Option<Watch> {
+        let mut watches = self.watches.lock().unwrap();
+        let entry = watches.get_mut(id)?;
+        let now = Utc::now();
+
+        entry.running = false;
+        entry.watch.next_run = next_run(now, entry.watch.interval_secs);
+
+        if let Some(job) = job {
+            entry.watch.last_run = Some(now);
+            entry.watch.last_scan_id = Some(job.id.clone());
+            entry.last = Some(job);
+        }
+
+        Some(entry.watch.clone())
+    }
+
+    // Envia um alerta ao webhook configurado
+    async fn notify(&self, webhook: &str, alert: &WatchAlert) {
+        let result = self.client
+            .post(webhook)
+            .json(alert)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => tracing::info!("Alerta de {} enviado para {}", alert.target, webhook),
+            Err(e) => tracing::warn!("Falha ao enviar alerta de {} para {}: {}", alert.target, webhook, e),
+        }
+    }
+}
+
+// Executa imediatamente o escaneamento de um alvo monitorado
+//
+// Retorna o alerta gerado, se o resultado trouxe novos subdomínios ou serviços.
+pub async fn run_watch(state: &Arc<AppState>, id: &str) -> Option<WatchAlert> {
+    let (watch, previous) = state.watchlist.begin(id)?;
+
+    tracing::info!("Monitoramento: escaneando {} ({})", watch.target, watch.id);
+
+    // Sem resultado em memória, usa o último escaneamento armazenado
+    let previous = match previous {
+        Some(previous) => Some(previous),
+        None => latest_stored_scan(state, &watch.target).await,
+    };
+
+    let scope = match watch.scope() {
+        Ok(scope) => scope,
+        Err(e) => {
+            tracing::error!("Monitoramento: escopo inválido para {}: {:#}", watch.target, e);
+            state.watchlist.complete(id, None);
+            return None;
+        }
+    };
+
+    let response = state.registry.scan(&scope).await;
+    let job = ScanJob::from_result(response);
+    state.persist(&job).await;
+
+    let alert = previous.and_then(|previous| {
+        let diff = diff_scans(&previous, &job);
+        if diff.added_subdomains.is_empty() && diff.new_services.is_empty() {
+            return None;
+        }
+        Some(WatchAlert {
+            event: "watch_alert".to_string(),
+            watch_id: watch.id.clone(),
+            target: watch.target.clone(),
+            from: diff.from,
+            to: diff.to,
+            timestamp: Utc::now(),
+            new_subdomains: diff.added_subdomains,
+            new_services: diff.new_services,
+        })
+    });
+
+    if let Some(updated) = state.watchlist.complete(id, Some(job)) {
+        save_watch(state, &updated).await;
+    }
+
+    if let Some(alert) = &alert {
+        tracing::info!(
+            "Monitoramento: {} novos subdomínios e {} novos serviços em {}",
+            alert.new_subdomains.len(),
+            alert.new_services.len(),
+            alert.target
+        );
+
+        match watch.webhook.as_ref().or(state.watchlist.default_webhook.as_ref()) {
+            Some(webhook) => state.watchlist.notify(webhook, alert).await,
+            None => tracing::warn!("Nenhum webhook configurado para alertas de {}", alert.target),
+        }
+    }
+
+    alert
+}
+
+// Inicia o agendador que reexecuta os alvos monitorados em segundo plano
+pub fn spawn_scheduler(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+        loop {
+            ticker.tick().await;
+
+            for id in state.watchlist.take_due(Utc::now()) {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    run_watch(&state, &id).await;
+                });
+            }
+        }
+    })
+}
+
+// Interpreta o intervalo de monitoramento
+//
+// Aceita atalhos no estilo cron (`@hourly`, `@daily`, `@weekly`) ou uma
+// duração como `30m`, `6h`, `1d`, opcionalmente precedida de `every`.
+pub fn parse_interval(interval: &str) -> Result<u64, String> {
+    let interval = interval.trim().to_lowercase();
+
+    let secs = match interval.as_str() {
+        "@hourly" => 3600,
+        "@daily" | "@midnight" => 86400,
+        "@weekly" => 7 * 86400,
+        _ => {
+            let value = interval.strip_prefix("every").unwrap_or(&interval).trim();
+            let split = value
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(|| format!("Intervalo sem unidade: {}", interval))?;
+            let (amount, unit) = value.split_at(split);
+
+            let amount: u64 = amount
+                .parse()
+                .map_err(|_| format!("Intervalo inválido: {}", interval))?;
+
+            let multiplier = match unit.trim() {
+                "s" => 1,
+                "m" | "min" => 60,
+                "h" => 3600,
+                "d" => 86400,
+                "w" => 7 * 86400,
+                _ => return Err(format!("Unidade de intervalo desconhecida: {}", interval)),
+            };
+
+            amount.saturating_mul(multiplier)
+        }
+    };
+
+    if secs < MIN_INTERVAL_SECS {
+        return Err(format!("Intervalo mínimo é de {} segundos", MIN_INTERVAL_SECS));
+    }
+    if secs > MAX_INTERVAL_SECS {
+        return Err(format!("Intervalo máximo é de {} dias", MAX_INTERVAL_SECS / 86400));
+    }
+
+    Ok(secs)
+}
+
+// Próxima execução a partir de `now`; intervalos fora dos limites (ex.: de um
+// monitoramento gravado por outra versão) ficam no máximo, sem estourar a data
+fn next_run(now: DateTime<Utc>, interval_secs: u64) -> DateTime<Utc> {
+    let secs = interval_secs.min(MAX_INTERVAL_SECS) as i64;
+    chrono::Duration::try_seconds(secs)
+        .and_then(|interval| now.checked_add_signed(interval))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+// Último escaneamento armazenado de um alvo, se houver histórico
+async fn latest_stored_scan(state: &AppState, target: &str) -> Option<ScanJob> {
+    #[cfg(feature = "sqlite")]
+    if let Some(storage) = &state.storage {
+        let latest = match storage.list_scans(Some(target.to_string())).await {
+            Ok(history) => history.into_iter().next(),
+            Err(e) => {
+                tracing::warn!("Falha ao consultar histórico de {}: {:#}", target, e);
+                None
+            }
+        };
+
+        if let Some(latest) = latest {
+            return storage.get_scan(&latest.id).await.ok().flatten();
+        }
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    let _ = (state, target);
+
+    None
+}
+
+// Persiste o estado de um alvo monitorado, se houver armazenamento
+pub async fn save_watch(state: &AppState, watch: &Watch) {
+    #[cfg(feature = "sqlite")]
+    if let Some(storage) = &state.storage {
+        if let Err(e) = storage.save_watch(watch).await {
+            tracing::error!("Falha ao persistir monitoramento {}: {:#}", watch.id, e);
+        }
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    let _ = (state, watch);
+}
@@ -0,0 +1,77 @@
+use hickory_proto::{
+    op::{Message, MessageType, ResponseCode},
+    rr::{rdata::CNAME, Name, RData, Record, RecordType},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+// Zona servida pelo DNS local: nome absoluto (com ponto final) -> registros
+pub type Zone = HashMap<&'static str, Vec<RData>>;
+
+pub fn name(value: &str) -> Name {
+    Name::from_str(value).unwrap()
+}
+
+// Sobe um servidor DNS UDP local que se comporta como um resolvedor recursivo:
+// segue a cadeia de CNAMEs e responde NXDOMAIN quando ela termina em um nome
+// inexistente. Retorna o endereço e o contador de consultas recebidas.
+pub async fn spawn_dns(zone: Zone) -> (SocketAddr, Arc<AtomicUsize>) {
+    let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    let queries = Arc::new(AtomicUsize::new(0));
+    let counter = queries.clone();
+
+    tokio::spawn(async move {
+        let mut buf = [0u8; 512];
+        loop {
+            let (len, peer) = socket.recv_from(&mut buf).await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let request = Message::from_vec(&buf[..len]).unwrap();
+            let query = request.queries()[0].clone();
+
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(request.op_code())
+                .set_recursion_desired(request.recursion_desired())
+                .set_recursion_available(true)
+                .add_query(query.clone());
+
+            let mut current = query.name().to_lowercase().to_ascii();
+            loop {
+                let Some(records) = zone.get(current.as_str()) else {
+                    response.set_response_code(ResponseCode::NXDomain);
+                    break;
+                };
+
+                let owner = name(&current);
+                let mut next = None;
+                for rdata in records {
+                    if let RData::CNAME(CNAME(target)) = rdata {
+                        next = Some(target.to_ascii());
+                    }
+                    if rdata.record_type() == query.query_type() || rdata.record_type() == RecordType::CNAME {
+                        response.add_answer(Record::from_rdata(owner.clone(), 60, rdata.clone()));
+                    }
+                }
+
+                // Consultas do tipo CNAME não seguem a cadeia
+                match next {
+                    Some(next) if query.query_type() != RecordType::CNAME => current = next,
+                    _ => break,
+                }
+            }
+
+            socket.send_to(&response.to_vec().unwrap(), peer).await.unwrap();
+        }
+    });
+
+    (addr, queries)
+}
@@ -0,0 +1,14 @@
+#![allow(dead_code)]
+
+use axum::Router;
+
+pub mod dns;
+
+// Sobe um servidor HTTP local em uma porta livre e retorna sua URL base
+pub async fn spawn_server(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}", addr)
+}
@@ -0,0 +1,168 @@
+use cortex_passivemap::config::Config;
+
+#[test]
+fn defaults_match_previous_hard_coded_values() {
+    let config = Config::from_toml("", Vec::new()).unwrap();
+
+    assert_eq!(config.server.bind, "127.0.0.1:3300");
+    assert_eq!(config.modules.crtsh.timeout_secs, 10);
+    assert_eq!(config.modules.shodan.max_host_lookups, 10);
+    assert_eq!(config.modules.wayback.limit, 500);
+    assert!(config.modules.dorker.enabled);
+    assert!(!config.dns.enabled);
+    assert_eq!(config.ipinfo.zone, "asn.cymru.com");
+}
+
+#[test]
+fn example_file_parses() {
+    let content = include_str!("../cortex.example.toml");
+    let config = Config::from_toml(content, Vec::new()).unwrap();
+
+    assert_eq!(config.modules.shodan.base_url, "https://api.shodan.io");
+    assert_eq!(config.http.rate_limits["crt.sh"].burst, 2);
+    assert_eq!(config.http.rate_limits["api.shodan.io"].per_second, 1.0);
+}
+
+#[test]
+fn rate_limits_merge_per_host() {
+    let content = r#"
+        [http.rate_limits]
+        "web.archive.org" = { per_second = 0.5 }
+    "#;
+    let config = Config::from_toml(content, Vec::new()).unwrap();
+
+    // Os limites padrão continuam valendo para os demais hosts
+    assert_eq!(config.http.rate_limits["web.archive.org"].per_second, 0.5);
+    assert_eq!(config.http.rate_limits["web.archive.org"].burst, 1);
+    assert!(config.http.rate_limits.contains_key("crt.sh"));
+    assert_eq!(config.http.max_retries, 3);
+}
+
+#[test]
+fn env_overrides_take_precedence_over_file() {
+    let content = r#"
+        [modules.shodan]
+        api_key = "from-file"
+        search_timeout_secs = 5
+
+        [modules.wayback]
+        enabled = false
+    "#;
+
+    let env = vec![
+        ("CORTEX__MODULES__SHODAN__API_KEY".to_string(), "12345".to_string()),
+        ("CORTEX__MODULES__WAYBACK__LIMIT".to_string(), "50".to_string()),
+        ("CORTEX__WATCH__WEBHOOK".to_string(), "http://127.0.0.1:9000/hook".to_string()),
+        ("CORTEX__DNS__NAMESERVERS".to_string(), "1.1.1.1, 9.9.9.9:53".to_string()),
+        ("UNRELATED".to_string(), "ignored".to_string()),
+    ];
+
+    let config = Config::from_toml(content, env).unwrap();
+
+    assert_eq!(config.modules.shodan.api_key, "12345");
+    assert_eq!(config.modules.shodan.search_timeout_secs, 5);
+    assert_eq!(config.modules.wayback.limit, 50);
+    assert!(!config.modules.wayback.enabled);
+    assert_eq!(config.watch.webhook.as_deref(), Some("http://127.0.0.1:9000/hook"));
+    assert_eq!(config.dns.nameservers, vec!["1.1.1.1", "9.9.9.9:53"]);
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let env = vec![("CORTEX__SCAN__MODULE_TIMEOUT_SECS".to_string(), "soon".to_string())];
+    assert!(Config::from_toml("", env).is_err());
+}
+
+#[test]
+fn float_overrides_and_new_rate_limit_hosts() {
+    let env = vec![
+        ("CORTEX__HTTP__RATE_LIMITS__CRT.SH__PER_SECOND".to_string(), "0.5".to_string()),
+        ("CORTEX__HTTP__RATE_LIMITS__WEB.ARCHIVE.ORG__PER_SECOND".to_string(), "2.5".to_string()),
+        ("CORTEX__HTTP__RATE_LIMITS__WEB.ARCHIVE.ORG__BURST".to_string(), "4".to_string()),
+    ];
+
+    let config = Config::from_toml("", env).unwrap();
+
+    assert_eq!(config.http.rate_limits["crt.sh"].per_second, 0.5);
+    assert_eq!(config.http.rate_limits["crt.sh"].burst, 2);
+    assert_eq!(config.http.rate_limits["web.archive.org"].per_second, 2.5);
+    assert_eq!(config.http.rate_limits["web.archive.org"].burst, 4);
+}
+
+#[test]
+fn unknown_env_keys_are_rejected() {
+    let env = vec![("CORTEX__MODULES__SHODAN__API_KY".to_string(), "12345".to_string())];
+    let error = Config::from_toml("", env).unwrap_err();
+    assert!(error.to_string().contains("modules.shodan.api_ky"), "{}", error);
+
+    let env = vec![("CORTEX__HTTPP__MAX_RETRIES".to_string(), "1".to_string())];
+    assert!(Config::from_toml("", env).is_err());
+}
+
+#[test]
+fn unusable_rate_limits_are_rejected() {
+    for limit in [
+        "{ per_second = 1e-20 }",
+        "{ per_second = 0.0 }",
+        "{ per_second = -1.0 }",
+        "{ per_second = nan }",
+        "{ per_second = inf }",
+        "{ per_second = 1.0, burst = 0 }",
+    ] {
+        let content = format!("[http.rate_limits]\n\"slow.example\" = {}", limit);
+        let error = Config::from_toml(&content, Vec::new()).unwrap_err();
+        assert!(error.to_string().contains("slow.example"), "{}: {}", limit, error);
+    }
+
+    let content = "[http.rate_limits]\n\"slow.example\" = { per_second = 0.001 }";
+    assert!(Config::from_toml(content, Vec::new()).is_ok());
+}
+
+#[test]
+fn legacy_env_aliases_override_the_file() {
+    let content = r#"
+        [storage]
+        path = "from-file.db"
+
+        [modules.shodan]
+        api_key = "from-file"
+    "#;
+
+    let env = vec![
+        ("SHODAN_API_KEY".to_string(), "legacy".to_string()),
+        ("CORTEX_DB_PATH".to_string(), "legacy.db".to_string()),
+        ("CORTEX_WEBHOOK_URL".to_string(), "http://127.0.0.1:9000/hook".to_string()),
+        ("CORTEX_MODULE_TIMEOUT".to_string(), "30".to_string()),
+        ("CORTEX_DISABLED_MODULES".to_string(), "Shodan, wayback machine,crtsh,desconhecido".to_string()),
+    ];
+
+    let config = Config::from_toml(content, env).unwrap();
+
+    assert_eq!(config.modules.shodan.api_key, "legacy");
+    assert_eq!(config.storage.path, "legacy.db");
+    assert_eq!(config.watch.webhook.as_deref(), Some("http://127.0.0.1:9000/hook"));
+    assert_eq!(config.scan.module_timeout_secs, 30);
+    assert!(!config.modules.shodan.enabled);
+    assert!(!config.modules.wayback.enabled);
+    assert!(!config.modules.crtsh.enabled);
+    assert!(config.modules.certspotter.enabled);
+    assert!(config.modules.dorker.enabled);
+}
+
+#[test]
+fn prefixed_env_wins_over_legacy_aliases() {
+    let env = vec![
+        ("CORTEX__MODULES__SHODAN__API_KEY".to_string(), "prefixed".to_string()),
+        ("SHODAN_API_KEY".to_string(), "legacy".to_string()),
+        ("CORTEX_MODULE_TIMEOUT".to_string(), "30".to_string()),
+        ("CORTEX__SCAN__MODULE_TIMEOUT_SECS".to_string(), "45".to_string()),
+        ("CORTEX_DISABLED_MODULES".to_string(), "shodan".to_string()),
+        ("CORTEX__MODULES__SHODAN__ENABLED".to_string(), "true".to_string()),
+    ];
+
+    let config = Config::from_toml("", env).unwrap();
+
+    assert_eq!(config.modules.shodan.api_key, "prefixed");
+    assert_eq!(config.scan.module_timeout_secs, 45);
+    assert!(config.modules.shodan.enabled);
+}
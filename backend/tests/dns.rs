@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use cortex_passivemap::{
+    config::{Config, DnsConfig, IpInfoConfig},
+    dns::DnsResolver,
+    ipinfo::IpInfoLookup,
+    registry::ModuleRegistry,
+    scope::Scope,
+    target::Target,
+    types::{IpInfo, ModuleResult, NetworkKind, Subdomain},
+    OsintModule,
+};
+use common::dns::{name, spawn_dns, Zone};
+use hickory_proto::rr::{
+    rdata::{A, AAAA, CNAME, PTR, TXT},
+    RData,
+};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{atomic::Ordering, Arc};
+
+mod common;
+
+fn zone() -> Zone {
+    Zone::from([
+        ("www.corp.example.", vec![RData::A(A("192.0.2.10".parse().unwrap()))]),
+        ("api.corp.example.", vec![RData::CNAME(CNAME(name("api.edge.cdn.test.")))]),
+        ("api.edge.cdn.test.", vec![RData::CNAME(CNAME(name("edge-1.cdn.test.")))]),
+        ("edge-1.cdn.test.", vec![
+            RData::A(A("198.51.100.7".parse().unwrap())),
+            RData::AAAA(AAAA("2001:db8::7".parse().unwrap())),
+        ]),
+        ("v6.corp.example.", vec![RData::AAAA(AAAA("2001:db8::1".parse().unwrap()))]),
+    ])
+}
+
+fn dns_config(addr: SocketAddr) -> DnsConfig {
+    DnsConfig {
+        enabled: true,
+        nameservers: vec![addr.to_string()],
+        timeout_secs: 2,
+        attempts: 1,
+        ..Default::default()
+    }
+}
+
+// Módulo que devolve subdomínios fixos, sem IP
+struct StaticModule;
+
+#[async_trait]
+impl OsintModule for StaticModule {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn scan(&self, target: &Target) -> anyhow::Result<ModuleResult> {
+        Ok(ModuleResult {
+            subdomains: ["www", "api", "v6", "gone"]
+                .iter()
+                .map(|label| Subdomain {
+                    name: format!("{}.{}", label, target),
+                    ip: None,
+                    cnames: Vec::new(),
+                    wildcard: false,
+                    first_seen: None,
+                    last_seen: None,
+                    issuer: None,
+                    certificates: Vec::new(),
+                    records: Vec::new(),
+                    source: "crt.sh".to_string(),
+                    sources: Vec::new(),
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+#[tokio::test]
+async fn resolves_addresses_and_cname_chain() {
+    let (addr, _) = spawn_dns(zone()).await;
+    let resolver = DnsResolver::from_config(&dns_config(addr)).unwrap();
+
+    let resolution = resolver.resolve("api.corp.example").await.unwrap();
+    assert_eq!(resolution.addresses, vec![
+        "198.51.100.7".parse::<IpAddr>().unwrap(),
+        "2001:db8::7".parse::<IpAddr>().unwrap(),
+    ]);
+    assert_eq!(resolution.cnames, vec!["api.edge.cdn.test", "edge-1.cdn.test"]);
+
+    assert!(resolver.resolve("gone.corp.example").await.is_none());
+}
+
+#[tokio::test]
+async fn scan_fills_subdomain_ips_and_target_ips() {
+    let (addr, _) = spawn_dns(zone()).await;
+    let mut registry = ModuleRegistry::new()
+        .with_resolver(DnsResolver::from_config(&dns_config(addr)).unwrap());
+    registry.register(Arc::new(StaticModule));
+
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    let subdomains: Vec<(&str, Option<&str>, Vec<&str>)> = response.subdomains
+        .iter()
+        .map(|s| (s.name.as_str(), s.ip.as_deref(), s.cnames.iter().map(String::as_str).collect()))
+        .collect();
+    assert_eq!(subdomains, vec![
+        ("api.corp.example", Some("198.51.100.7"), vec!["api.edge.cdn.test", "edge-1.cdn.test"]),
+        ("gone.corp.example", None, vec![]),
+        ("v6.corp.example", Some("2001:db8::1"), vec![]),
+        ("www.corp.example", Some("192.0.2.10"), vec![]),
+    ]);
+
+    let ips: Vec<&str> = response.ips.iter().map(|info| info.ip.as_str()).collect();
+    assert_eq!(ips, vec!["192.0.2.10", "198.51.100.7", "2001:db8::1", "2001:db8::7"]);
+    assert!(response.ips.iter().all(|info| info.asn.is_none()));
+}
+
+#[tokio::test]
+async fn resolution_is_disabled_by_default() {
+    let (addr, queries) = spawn_dns(zone()).await;
+    let mut config = Config::default();
+    config.dns.nameservers = vec![addr.to_string()];
+    config.modules.crtsh.enabled = false;
+    config.modules.certspotter.enabled = false;
+    config.modules.shodan.enabled = false;
+    config.modules.wayback.enabled = false;
+    config.modules.dorker.enabled = false;
+
+    let mut registry = ModuleRegistry::from_config(&config);
+    registry.register(Arc::new(StaticModule));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    assert!(response.subdomains.iter().all(|s| s.ip.is_none()));
+    assert_eq!(queries.load(Ordering::SeqCst), 0);
+}
+
+// Módulo que encontra um IP fora do bloco escaneado
+struct NetworkModule;
+
+#[async_trait]
+impl OsintModule for NetworkModule {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    async fn scan(&self, _target: &Target) -> anyhow::Result<ModuleResult> {
+        Ok(ModuleResult { ips: vec!["198.51.100.7".to_string()], ..Default::default() })
+    }
+}
+
+#[tokio::test]
+async fn network_targets_get_ptr_names_grouped_by_ip() {
+    let ptr = |target: &str| vec![RData::PTR(PTR(name(target)))];
+    let (addr, _) = spawn_dns(Zone::from([
+        ("10.2.0.192.in-addr.arpa.", ptr("mail.corp.example.")),
+        ("9.2.0.192.in-addr.arpa.", ptr("edge.cdn.test.")),
+        ("7.100.51.198.in-addr.arpa.", ptr("outside.cdn.test.")),
+    ]))
+    .await;
+
+    let mut registry = ModuleRegistry::new()
+        .with_resolver(DnsResolver::from_config(&dns_config(addr)).unwrap());
+    registry.register(Arc::new(NetworkModule));
+
+    let scope = Scope::new("192.0.2.8/30".parse().unwrap())
+        .unwrap()
+        .with_patterns(&[], &["edge.cdn.test".to_string()])
+        .unwrap();
+    let response = registry.scan(&scope).await;
+
+    // Apenas nomes de IPs do bloco, menos os excluídos
+    let subdomains: Vec<(&str, Option<&str>, &str)> = response.subdomains
+        .iter()
+        .map(|s| (s.name.as_str(), s.ip.as_deref(), s.source.as_str()))
+        .collect();
+    assert_eq!(subdomains, vec![("mail.corp.example", Some("192.0.2.10"), "PTR")]);
+    assert_eq!(response.target, "192.0.2.8/30");
+}
+
+// Módulo que encontra o IP de uma borda de CDN e um IP próprio do alvo
+struct AddressModule;
+
+#[async_trait]
+impl OsintModule for AddressModule {
+    fn name(&self) -> &'static str {
+        "addresses"
+    }
+
+    async fn scan(&self, _target: &Target) -> anyhow::Result<ModuleResult> {
+        // O escopo só mantém IPs ligados a nomes do alvo
+        let hosts = [("www.corp.example", "104.16.1.1"), ("mail.corp.example", "192.0.2.10"), ("vpn.corp.example", "198.51.100.99")];
+        Ok(ModuleResult {
+            subdomains: hosts
+                .iter()
+                .map(|(name, ip)| Subdomain {
+                    name: name.to_string(),
+                    ip: Some(ip.to_string()),
+                    cnames: Vec::new(),
+                    wildcard: false,
+                    first_seen: None,
+                    last_seen: None,
+                    issuer: None,
+                    certificates: Vec::new(),
+                    records: Vec::new(),
+                    source: self.name().to_string(),
+                    sources: Vec::new(),
+                })
+                .collect(),
+            ips: hosts.iter().map(|(_, ip)| ip.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+}
+
+#[tokio::test]
+async fn ips_get_asn_country_and_provider() {
+    let txt = |record: &str| vec![RData::TXT(TXT::new(vec![record.to_string()]))];
+    let (addr, _) = spawn_dns(Zone::from([
+        ("1.1.16.104.origin.asn.test.", vec![
+            RData::TXT(TXT::new(vec!["13335 | 104.16.0.0/12 | US | arin | 2014-03-28".to_string()])),
+            RData::TXT(TXT::new(vec!["13335 | 104.16.0.0/13 | US | arin | 2014-03-28".to_string()])),
+        ]),
+        ("10.2.0.192.origin.asn.test.", txt("64500 | 192.0.2.0/24 | BR | lacnic | 2019-05-02")),
+        ("as13335.asn.test.", txt("13335 | US | arin | 2010-07-14 | CLOUDFLARENET - Cloudflare, Inc., US")),
+        ("as64500.asn.test.", txt("64500 | BR | lacnic | 2019-05-02 | CORP-AS - Corp S.A., BR")),
+    ]))
+    .await;
+
+    let config = IpInfoConfig { zone: "asn.test".to_string(), ..Default::default() };
+    let mut registry = ModuleRegistry::new()
+        .with_resolver(DnsResolver::from_config(&dns_config(addr)).unwrap())
+        .with_ipinfo(IpInfoLookup::from_config(&config).unwrap());
+    registry.register(Arc::new(AddressModule));
+
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    assert_eq!(response.ips, vec![
+        IpInfo {
+            asn: Some(13335),
+            as_org: Some("CLOUDFLARENET - Cloudflare, Inc., US".to_string()),
+            network: Some("104.16.0.0/13".to_string()),
+            country: Some("US".to_string()),
+            provider: Some("Cloudflare".to_string()),
+            kind: Some(NetworkKind::Cdn),
+            ..IpInfo::new("104.16.1.1")
+        },
+        IpInfo {
+            asn: Some(64500),
+            as_org: Some("CORP-AS - Corp S.A., BR".to_string()),
+            network: Some("192.0.2.0/24".to_string()),
+            country: Some("BR".to_string()),
+            ..IpInfo::new("192.0.2.10")
+        },
+        IpInfo::new("198.51.100.99"),
+    ]);
+
+    let edges: Vec<&str> = response.ips.iter().filter(|info| info.is_edge()).map(|info| info.ip.as_str()).collect();
+    assert_eq!(edges, vec!["104.16.1.1"]);
+}
+
+#[test]
+fn stored_scans_with_bare_ips_still_load() {
+    let response: cortex_passivemap::types::TargetResponse = serde_json::from_value(serde_json::json!({
+        "target": "corp.example",
+        "timestamp": "2024-01-01T00:00:00Z",
+        "subdomains": [],
+        "ips": ["192.0.2.10", {"ip": "104.16.1.1", "asn": 13335, "kind": "cdn"}],
+        "services": [],
+        "urls": [],
+        "dorks": [],
+        "modules": []
+    }))
+    .unwrap();
+
+    assert_eq!(response.ips[0], IpInfo::new("192.0.2.10"));
+    assert_eq!(response.ips[1].asn, Some(13335));
+    assert!(response.ips[1].is_edge());
+}
@@ -0,0 +1,917 @@
+use axum::{
+    extract::{Path, Query, RawQuery},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use cortex_passivemap::{
+    config::{CertSpotterConfig, CrtShConfig, HttpConfig, ShodanConfig, WaybackConfig},
+    http::{HttpClient, HttpErrorKind},
+    modules::{
+        certspotter::CertSpotterModule, crtsh::CrtShModule, shodan::ShodanModule, wayback::WaybackModule,
+    },
+    registry::ModuleRegistry,
+    scope::Scope,
+    target::Target,
+    output::render_table,
+    types::{CreditUsage, HttpInfo, ModuleSkipped, ModuleStatus},
+    OsintModule,
+};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[tokio::test]
+async fn crtsh_parses_certificate_entries() {
+    let app = Router::new().route(
+        "/",
+        get(|RawQuery(query): RawQuery| async move {
+            assert!(query.unwrap_or_default().contains("corp.example"));
+            Json(json!([
+                {
+                    "id": 101,
+                    "issuer_name": "C=US, O=Let's Encrypt, CN=R3",
+                    "common_name": "www.corp.example",
+                    "serial_number": "0a01",
+                    "entry_timestamp": "2024-01-01T00:10:00.123",
+                    "name_value": "www.corp.example",
+                    "not_before": "2024-01-01T00:00:00",
+                    "not_after": "2024-04-01T00:00:00"
+                },
+                {
+                    "id": 102,
+                    "issuer_name": "C=US, O=DigiCert Inc, CN=DigiCert TLS RSA SHA256 2020 CA1",
+                    "name_value": "www.corp.example",
+                    "not_before": "2024-03-01T00:00:00",
+                    "not_after": "2025-03-01T00:00:00"
+                },
+                {
+                    "id": 104,
+                    "issuer_name": "C=US, O=Let's Encrypt, CN=R3",
+                    "name_value": "corp.example\n*.corp.example\nWWW.corp.example.\nevilcorp.example",
+                    "not_before": "2023-01-01T00:00:00",
+                    "not_after": "2023-12-31T00:00:00"
+                },
+                {
+                    "name_value": "*.api.corp.example",
+                    "not_before": "2024-02-01T00:00:00",
+                    "not_after": null
+                },
+                {
+                    "id": 103,
+                    "name_value": "unrelated.example",
+                    "not_before": null,
+                    "not_after": null
+                }
+            ]))
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let module = CrtShModule::new(CrtShConfig { base_url, ..Default::default() }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    // Cada SAN vira um nome; evilcorp.example não é subdomínio do alvo
+    let names: Vec<(&str, bool)> = result.subdomains.iter().map(|s| (s.name.as_str(), s.wildcard)).collect();
+    assert_eq!(names, vec![
+        ("api.corp.example", true),
+        ("corp.example", true),
+        ("www.corp.example", false),
+    ]);
+
+    // Datas agregadas entre os três certificados de www
+    let www = result.subdomains.iter().find(|s| s.name == "www.corp.example").unwrap();
+    assert_eq!(www.first_seen.unwrap().to_rfc3339(), "2023-01-01T00:00:00+00:00");
+    assert_eq!(www.last_seen.unwrap().to_rfc3339(), "2025-03-01T00:00:00+00:00");
+    assert_eq!(www.source, "crt.sh");
+    assert!(www.ip.is_none());
+
+    // Os certificados de www ficam ligados ao subdomínio
+    assert_eq!(www.certificates, vec!["101", "102", "104"]);
+    let api = result.subdomains.iter().find(|s| s.name == "api.corp.example").unwrap();
+    assert!(api.certificates.is_empty());
+
+    let issuers: Vec<(&str, Option<&str>)> = result.certificates
+        .iter()
+        .map(|c| (c.id.as_str(), c.issuer.as_deref()))
+        .collect();
+    assert_eq!(issuers, vec![
+        ("101", Some("C=US, O=Let's Encrypt, CN=R3")),
+        ("102", Some("C=US, O=DigiCert Inc, CN=DigiCert TLS RSA SHA256 2020 CA1")),
+        ("104", Some("C=US, O=Let's Encrypt, CN=R3")),
+    ]);
+    assert_eq!(result.certificates[2].names, vec![
+        "corp.example",
+        "*.corp.example",
+        "www.corp.example",
+        "evilcorp.example",
+    ]);
+    let first = &result.certificates[0];
+    assert_eq!(first.serial_number.as_deref(), Some("0a01"));
+    assert_eq!(first.logged_at.unwrap().to_rfc3339(), "2024-01-01T00:10:00.123+00:00");
+    assert!(first.is_expired(chrono::Utc::now()));
+}
+
+#[tokio::test]
+async fn crtsh_reports_upstream_errors() {
+    let app = Router::new().route("/", get(|| async { StatusCode::BAD_GATEWAY }));
+    let base_url = common::spawn_server(app).await;
+
+    let module = CrtShModule::new(CrtShConfig { base_url, ..Default::default() }, HttpClient::default());
+    let error = module.scan(&"corp.example".parse().unwrap()).await.unwrap_err();
+
+    assert!(error.to_string().contains("502"), "{}", error);
+}
+
+#[tokio::test]
+async fn partial_failures_are_reported_with_the_data() {
+    let app = Router::new().route(
+        "/",
+        get(|Query(params): Query<HashMap<String, String>>| async move {
+            // Só a consulta de segundo nível falha
+            if params["q"].starts_with("%.%.") {
+                return StatusCode::NOT_FOUND.into_response();
+            }
+            Json(json!([{
+                "issuer_name": "C=US, O=Let's Encrypt, CN=R3",
+                "common_name": "www.corp.example",
+                "name_value": "www.corp.example",
+                "id": 1,
+                "entry_timestamp": "2024-01-01T00:00:00.000",
+                "not_before": "2024-01-01T00:00:00",
+                "not_after": "2024-03-31T00:00:00"
+            }]))
+            .into_response()
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(CrtShModule::new(CrtShConfig { base_url, ..Default::default() }, HttpClient::default())));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    let report = &response.modules[0];
+    assert_eq!(report.status, ModuleStatus::Partial);
+    assert_eq!(report.counts.subdomains, 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("404"), "{:?}", report.warnings);
+    assert!(report.error.is_none());
+
+    let table = render_table(&response);
+    assert!(table.contains("parcial"), "{}", table);
+    assert!(table.contains("falhas parciais"), "{}", table);
+}
+
+#[tokio::test]
+async fn throttled_module_reports_a_structured_http_error() {
+    let calls = Arc::new(Mutex::new(0));
+    let counter = calls.clone();
+    let app = Router::new().route(
+        "/",
+        get(move || {
+            let counter = counter.clone();
+            async move {
+                *counter.lock().unwrap() += 1;
+                (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "0")])
+            }
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let http = HttpClient::from_config(&HttpConfig { max_retries: 1, backoff_ms: 1, ..Default::default() });
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(CrtShModule::new(CrtShConfig { base_url, ..Default::default() }, http)));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    // Cada uma das três consultas tenta duas vezes
+    assert_eq!(*calls.lock().unwrap(), 6);
+
+    let report = &response.modules[0];
+    assert_eq!(report.status, ModuleStatus::Failed);
+    let error = report.http_error.as_ref().expect("erro HTTP estruturado");
+    assert_eq!(error.kind, HttpErrorKind::RateLimited);
+    assert_eq!((error.status, error.attempts, error.host.as_str()), (Some(429), 2, "127.0.0.1"));
+    assert_eq!(report.error.as_deref(), Some("127.0.0.1 limitou as requisições (status 429) após 2 tentativas; o servidor pediu 0s de espera"));
+
+    let json = serde_json::to_value(report).unwrap();
+    assert_eq!(json["http_error"]["kind"], "rate_limited");
+}
+
+#[tokio::test]
+async fn certificates_are_ordered_by_numeric_id() {
+    let app = Router::new().route(
+        "/",
+        get(|| async {
+            Json(json!([
+                { "id": 10, "name_value": "www.corp.example" },
+                { "id": 9, "name_value": "www.corp.example" },
+                { "id": 100, "name_value": "www.corp.example" }
+            ]))
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(CrtShModule::new(
+        CrtShConfig { base_url, ..Default::default() },
+        HttpClient::default(),
+    )));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    // Como texto, "10" e "100" viriam antes de "9"
+    let ids: Vec<&str> = response.certificates.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec!["9", "10", "100"]);
+    assert_eq!(response.subdomains[0].certificates, vec!["9", "10", "100"]);
+}
+
+#[tokio::test]
+async fn certspotter_results_merge_with_crtsh_by_name() {
+    let app = Router::new()
+        .route(
+            "/",
+            get(|| async {
+                Json(json!([{
+                    "id": 1001,
+                    "issuer_name": "C=US, O=Let's Encrypt, CN=R3",
+                    "name_value": "www.corp.example",
+                    "not_before": "2023-06-01T00:00:00",
+                    "not_after": "2023-08-30T00:00:00"
+                }]))
+            }),
+        )
+        .route(
+            "/v1/issuances",
+            get(|headers: HeaderMap, RawQuery(query): RawQuery| async move {
+                assert_eq!(headers["authorization"], "Bearer cs-key");
+                let query = query.unwrap_or_default();
+                assert!(query.contains("domain=corp.example"));
+                if query.contains("after=") {
+                    return Json(json!([]));
+                }
+                Json(json!([{
+                    "id": "1001",
+                    "dns_names": ["www.corp.example", "mail.corp.example"],
+                    "issuer": { "name": "C=US, O=Let's Encrypt, CN=R3", "friendly_name": "Let's Encrypt" },
+                    "not_before": "2024-01-01T00:00:00Z",
+                    "not_after": "2024-03-31T00:00:00Z"
+                }]))
+            }),
+        );
+    let base_url = common::spawn_server(app).await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(CrtShModule::new(
+        CrtShConfig { base_url: base_url.clone(), ..Default::default() },
+        HttpClient::default(),
+    )));
+    registry.register(Arc::new(CertSpotterModule::new(
+        CertSpotterConfig { base_url, api_key: "cs-key".to_string(), ..Default::default() },
+        HttpClient::default(),
+    )));
+
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    let names: Vec<(&str, Vec<&str>)> = response.subdomains
+        .iter()
+        .map(|s| (s.name.as_str(), s.sources()))
+        .collect();
+    assert_eq!(names, vec![
+        ("mail.corp.example", vec!["certspotter"]),
+        ("www.corp.example", vec!["certspotter", "crt.sh"]),
+    ]);
+
+    let www = &response.subdomains[1];
+    assert_eq!(www.first_seen.unwrap().to_rfc3339(), "2023-06-01T00:00:00+00:00");
+    assert_eq!(www.last_seen.unwrap().to_rfc3339(), "2024-03-31T00:00:00+00:00");
+    assert_eq!(www.issuer.as_deref(), Some("C=US, O=Let's Encrypt, CN=R3"));
+
+    // `source` continua sendo uma única fonte
+    assert_eq!(www.source, "certspotter");
+
+    // Os certificados das duas fontes aparecem lado a lado, mesmo com o mesmo id na origem
+    let sources: BTreeSet<&str> = response.certificates.iter().map(|c| c.source.as_str()).collect();
+    assert_eq!(sources, BTreeSet::from(["certspotter", "crt.sh"]));
+    assert_eq!(www.certificates, vec!["1001", "certspotter:1001"]);
+}
+
+#[tokio::test]
+async fn shodan_merges_search_and_host_details() {
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(params.get("key").map(String::as_str), Some("test-key"));
+                assert!(params["query"].starts_with("hostname:corp.example"));
+                Json(json!({
+                    "total": 1,
+                    "matches": [{
+                        "ip_str": "192.0.2.10",
+                        "port": 443,
+                        "transport": "tcp",
+                        "product": "nginx",
+                        "version": "1.25.3",
+                        "data": "HTTP/1.1 200 OK",
+                        "hostnames": ["www.corp.example"],
+                        "domains": ["corp.example"]
+                    }]
+                }))
+            }),
+        )
+        .route(
+            "/shodan/host/:ip",
+            get(|Path(ip): Path<String>| async move {
+                assert_eq!(ip, "192.0.2.10");
+                Json(json!({
+                    "ip_str": "192.0.2.10",
+                    "ports": [22, 443],
+                    "hostnames": ["vpn.corp.example"],
+                    "data": [
+                        { "port": 22, "transport": "tcp", "product": "OpenSSH", "data": "SSH-2.0-OpenSSH_9.6" },
+                        { "port": 443, "transport": "tcp", "product": "nginx", "version": "1.25.3", "data": "HTTP/1.1 200 OK" }
+                    ]
+                }))
+            }),
+        );
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    assert_eq!(result.ips, vec!["192.0.2.10".to_string()]);
+
+    let mut ports: Vec<(u16, &str)> = result.services
+        .iter()
+        .map(|s| (s.port, s.service.as_str()))
+        .collect();
+    ports.sort();
+    ports.dedup();
+    assert_eq!(ports, vec![(22, "OpenSSH"), (443, "nginx 1.25.3")]);
+
+    let mut hostnames: Vec<(&str, Option<&str>)> = result.subdomains
+        .iter()
+        .map(|s| (s.name.as_str(), s.ip.as_deref()))
+        .collect();
+    hostnames.sort();
+    assert_eq!(hostnames, vec![
+        ("vpn.corp.example", Some("192.0.2.10")),
+        ("www.corp.example", Some("192.0.2.10")),
+    ]);
+}
+
+#[tokio::test]
+async fn shodan_keeps_every_port_and_follows_pages() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(move |Query(params): Query<HashMap<String, String>>| {
+                let log = log.clone();
+                async move {
+                    let query = params["query"].clone();
+                    let page: u16 = params.get("page").map_or(1, |page| page.parse().unwrap());
+                    log.lock().unwrap().push((query.clone(), page));
+
+                    let service = |port: u16, transport: &str| json!({
+                        "ip_str": "192.0.2.10",
+                        "port": port,
+                        "transport": transport
+                    });
+                    // A busca base tem 150 serviços no mesmo IP, em duas páginas
+                    let matches: Vec<Value> = if query.contains("+port:") {
+                        vec![service(80, "tcp"), service(80, "udp")]
+                    } else if query.contains("+has:") {
+                        Vec::new()
+                    } else if page == 1 {
+                        (1..=100).map(|port| service(port, "tcp")).collect()
+                    } else {
+                        (101..=150).map(|port| service(port, "tcp")).collect()
+                    };
+                    Json(json!({ "total": 150, "matches": matches }))
+                }
+            }),
+        )
+        .route("/shodan/host/:ip", get(|| async { StatusCode::NOT_FOUND }));
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        max_pages: 5,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    // Portas 1-150 em TCP mais a 80 em UDP
+    assert_eq!(result.services.len(), 151);
+    assert!(result.services.iter().any(|s| s.port == 80 && s.service == "udp"));
+    assert_eq!(result.ips, vec!["192.0.2.10".to_string()]);
+
+    // Páginas só da busca base, e apenas até completar o total
+    let mut requests = requests.lock().unwrap().clone();
+    requests.retain(|(query, _)| query == "hostname:corp.example");
+    requests.sort();
+    assert_eq!(requests, vec![
+        ("hostname:corp.example".to_string(), 1),
+        ("hostname:corp.example".to_string(), 2),
+    ]);
+}
+
+#[tokio::test]
+async fn shodan_matches_for_other_domains_leave_no_ips_or_services() {
+    // `hostname:corp.example` também casa com evilcorp.example
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(|| async {
+                Json(json!({
+                    "total": 2,
+                    "matches": [
+                        { "ip_str": "192.0.2.10", "port": 443, "transport": "tcp", "hostnames": ["www.corp.example"] },
+                        { "ip_str": "203.0.113.5", "port": 22, "transport": "tcp", "hostnames": ["evilcorp.example"] }
+                    ]
+                }))
+            }),
+        )
+        .route("/shodan/host/:ip", get(|| async { StatusCode::NOT_FOUND }));
+    let base_url = common::spawn_server(app).await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        dns_lookups: false,
+        ..Default::default()
+    }, HttpClient::default())));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    let ips: Vec<&str> = response.ips.iter().map(|info| info.ip.as_str()).collect();
+    assert_eq!(ips, vec!["192.0.2.10"]);
+    let services: Vec<(&str, u16)> = response.services.iter().map(|s| (s.ip.as_str(), s.port)).collect();
+    assert_eq!(services, vec![("192.0.2.10", 443)]);
+}
+
+#[tokio::test]
+async fn shodan_host_details_follow_ip_order() {
+    let lookups = Arc::new(Mutex::new(Vec::new()));
+    let log = lookups.clone();
+
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(|| async {
+                let matches: Vec<Value> = ["192.0.2.30", "192.0.2.10", "192.0.2.40", "192.0.2.20"]
+                    .iter()
+                    .map(|ip| json!({ "ip_str": ip, "port": 443, "transport": "tcp", "hostnames": ["www.corp.example"] }))
+                    .collect();
+                Json(json!({ "total": 4, "matches": matches }))
+            }),
+        )
+        .route(
+            "/shodan/host/:ip",
+            get(move |Path(ip): Path<String>| {
+                let log = log.clone();
+                async move {
+                    log.lock().unwrap().push(ip);
+                    StatusCode::NOT_FOUND
+                }
+            }),
+        );
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        dns_lookups: false,
+        max_host_lookups: 2,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    // Os mesmos IPs a cada execução, e não os que a ordem de um HashSet sortear
+    let mut lookups = lookups.lock().unwrap().clone();
+    lookups.sort();
+    assert_eq!(lookups, vec!["192.0.2.10", "192.0.2.20"]);
+    assert_eq!(result.ips, vec!["192.0.2.10", "192.0.2.20", "192.0.2.30", "192.0.2.40"]);
+}
+
+#[tokio::test]
+async fn shodan_stops_paid_searches_when_credits_run_out() {
+    let searches = Arc::new(Mutex::new(0));
+    let counter = searches.clone();
+
+    let app = Router::new()
+        .route("/api-info", get(|| async { Json(json!({ "query_credits": 2, "plan": "dev" })) }))
+        .route(
+            "/shodan/host/search",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    *counter.lock().unwrap() += 1;
+                    Json(json!({
+                        "total": 1,
+                        "matches": [{ "ip_str": "192.0.2.10", "port": 443, "transport": "tcp", "hostnames": ["corp.example"] }]
+                    }))
+                }
+            }),
+        )
+        .route("/shodan/host/:ip", get(|| async { StatusCode::NOT_FOUND }));
+    let base_url = common::spawn_server(app).await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        credit_budget: 5,
+        // Só as buscas disputam os créditos
+        dns_lookups: false,
+        ..Default::default()
+    }, HttpClient::default())));
+    let response = registry.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    // O saldo da conta (2) é menor que o orçamento (5): a terceira busca não é feita
+    assert_eq!(*searches.lock().unwrap(), 2);
+    assert_eq!(response.modules[0].usage, Some(CreditUsage {
+        used: 2,
+        remaining: Some(2),
+        budget: Some(5),
+        skipped: 1,
+    }));
+    assert_eq!(response.services.len(), 1);
+
+    let table = render_table(&response);
+    assert!(table.contains("2 créditos usados, 0 restantes, 1 consultas puladas pelo orçamento"), "{}", table);
+}
+
+#[tokio::test]
+async fn shodan_dns_lists_subdomains_with_records() {
+    let resolved = Arc::new(Mutex::new(Vec::new()));
+    let log = resolved.clone();
+
+    let app = Router::new()
+        .route("/api-info", get(|| async { Json(json!({ "query_credits": 10 })) }))
+        .route(
+            "/dns/domain/:domain",
+            get(|Path(domain): Path<String>, Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(domain, "corp.example");
+                // Registros em duas páginas
+                let data = match params.get("page").map(String::as_str) {
+                    None => json!([
+                        { "subdomain": "", "type": "A", "value": "192.0.2.1" },
+                        { "subdomain": "www", "type": "CNAME", "value": "corp.edge.cdn.test" },
+                        { "subdomain": "www", "type": "A", "value": "198.51.100.7", "last_seen": "2024-03-01T12:00:00.000000" },
+                        { "subdomain": "*.apps", "type": "A", "value": "192.0.2.40" }
+                    ]),
+                    Some("2") => json!([
+                        { "subdomain": "Mail", "type": "MX", "value": "mx.mailhost.test" },
+                        { "subdomain": "www", "type": "AAAA", "value": "2001:db8::7" }
+                    ]),
+                    Some(page) => panic!("página inesperada {}", page),
+                };
+                Json(json!({ "domain": domain, "data": data, "more": !params.contains_key("page") }))
+            }),
+        )
+        .route(
+            "/dns/resolve",
+            get(move |Query(params): Query<HashMap<String, String>>| {
+                let log = log.clone();
+                async move {
+                    let hostnames = params["hostnames"].clone();
+                    log.lock().unwrap().push(hostnames.clone());
+                    Json(json!({ hostnames: "192.0.2.25" }))
+                }
+            }),
+        )
+        .route(
+            "/dns/reverse",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(params["ips"], "192.0.2.10");
+                Json(json!({ "192.0.2.10": ["vpn.corp.example", "corp.example", "host.isp.test"] }))
+            }),
+        )
+        .route(
+            "/shodan/host/search",
+            get(|| async {
+                Json(json!({
+                    "total": 1,
+                    "matches": [{
+                        "ip_str": "192.0.2.10",
+                        "port": 443,
+                        "transport": "tcp",
+                        "hostnames": ["vpn.corp.example"]
+                    }]
+                }))
+            }),
+        )
+        .route("/shodan/host/:ip", get(|| async { StatusCode::NOT_FOUND }));
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    let mut subdomains: Vec<(&str, Option<&str>, String)> = result.subdomains
+        .iter()
+        .map(|s| (s.name.as_str(), s.ip.as_deref(), s.sources().join(", ")))
+        .collect();
+    subdomains.sort();
+    assert_eq!(subdomains, vec![
+        ("apps.corp.example", Some("192.0.2.40"), "Shodan DNS".to_string()),
+        ("corp.example", Some("192.0.2.10"), "Shodan DNS".to_string()),
+        ("mail.corp.example", Some("192.0.2.25"), "Shodan DNS".to_string()),
+        ("vpn.corp.example", Some("192.0.2.10"), "Shodan, Shodan DNS".to_string()),
+        ("www.corp.example", Some("198.51.100.7"), "Shodan DNS".to_string()),
+    ]);
+
+    let www = result.subdomains.iter().find(|s| s.name == "www.corp.example").unwrap();
+    assert_eq!(www.cnames, vec!["corp.edge.cdn.test".to_string()]);
+    let records: Vec<(&str, &str)> = www.records
+        .iter()
+        .map(|record| (record.record_type.as_str(), record.value.as_str()))
+        .collect();
+    assert_eq!(records, vec![
+        ("CNAME", "corp.edge.cdn.test"),
+        ("A", "198.51.100.7"),
+        ("AAAA", "2001:db8::7"),
+    ]);
+    assert!(result.subdomains.iter().any(|s| s.name == "apps.corp.example" && s.wildcard));
+
+    // Só o nome sem registro A/AAAA é resolvido
+    assert_eq!(*resolved.lock().unwrap(), vec!["mail.corp.example".to_string()]);
+
+    // Duas páginas de DNS e três buscas
+    assert_eq!(result.usage.map(|usage| usage.used), Some(5));
+}
+
+#[tokio::test]
+async fn shodan_summarizes_facets_without_spending_credits() {
+    let app = Router::new()
+        .route("/api-info", get(|| async { Json(json!({ "query_credits": 0 })) }))
+        .route("/shodan/host/search", get(|| async { panic!("busca paga sem créditos") as StatusCode }))
+        .route(
+            "/shodan/host/count",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(params["query"], "net:192.0.2.0/24");
+                assert_eq!(params["facets"], "port,product,org,country");
+                Json(json!({
+                    "total": 12,
+                    "matches": [],
+                    "facets": {
+                        "country": [{ "count": 12, "value": "BR" }],
+                        "port": [{ "count": 9, "value": 443 }, { "count": 3, "value": 22 }]
+                    }
+                }))
+            }),
+        );
+    let base_url = common::spawn_server(app).await;
+
+    // Sem saldo, o modo de busca recorre às contagens
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"192.0.2.0/24".parse().unwrap()).await.unwrap();
+
+    let facets: Vec<(&str, &str, u64)> = result.facets
+        .iter()
+        .map(|f| (f.facet.as_str(), f.value.as_str(), f.count))
+        .collect();
+    assert_eq!(facets, vec![("port", "443", 9), ("port", "22", 3), ("country", "BR", 12)]);
+    assert!(result.services.is_empty());
+    assert_eq!(result.usage.map(|usage| usage.used), Some(0));
+}
+
+#[tokio::test]
+async fn shodan_services_carry_http_tls_and_vulnerabilities() {
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(|| async {
+                Json(json!({
+                    "total": 1,
+                    "matches": [{
+                        "ip_str": "192.0.2.10",
+                        "port": 443,
+                        "transport": "tcp",
+                        "product": "nginx",
+                        "version": "1.20.0",
+                        "timestamp": "2024-03-01T12:34:56.789012",
+                        "cpe23": ["cpe:2.3:a:f5:nginx:1.20.0"],
+                        "cpe": ["cpe:/a:f5:nginx:1.20.0"],
+                        "http": {
+                            "status": 200,
+                            "title": "Corp VPN - Login",
+                            "server": "nginx/1.20.0",
+                            "redirects": [{ "host": "corp.example", "location": "https://vpn.corp.example/login" }],
+                            "favicon": { "hash": -1137974287, "location": "https://vpn.corp.example/favicon.ico" }
+                        },
+                        "ssl": {
+                            "cert": {
+                                "subject": { "CN": "vpn.corp.example" },
+                                "issuer": { "C": "US", "O": "Let's Encrypt", "CN": "R3" },
+                                "expires": "20240531000000Z",
+                                "fingerprint": { "sha1": "aa", "sha256": "bb" }
+                            },
+                            "versions": ["-SSLv3", "TLSv1.2", "TLSv1.3"],
+                            "cipher": { "name": "TLS_AES_256_GCM_SHA384", "bits": 256 },
+                            "jarm": "27d40d40d29d40d1dc42d43d00041d"
+                        },
+                        "vulns": {
+                            "CVE-2021-3618": { "verified": false, "cvss": "5.8", "summary": "ALPACA" },
+                            "CVE-2021-23017": { "verified": true, "cvss": 7.7, "summary": "Off-by-one no resolver" }
+                        }
+                    }]
+                }))
+            }),
+        )
+        .route(
+            "/shodan/host/:ip",
+            get(|| async {
+                // Respostas de host podem trazer só os ids das CVEs
+                Json(json!({
+                    "ip_str": "192.0.2.10",
+                    "data": [{ "port": 22, "transport": "tcp", "product": "OpenSSH", "vulns": ["CVE-2023-48795"] }]
+                }))
+            }),
+        );
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    let https = result.services.iter().find(|s| s.port == 443).unwrap();
+    assert_eq!(https.service, "nginx 1.20.0");
+    assert_eq!(https.cpes, vec!["cpe:2.3:a:f5:nginx:1.20.0"]);
+    assert_eq!(https.last_seen.unwrap().to_rfc3339(), "2024-03-01T12:34:56.789012+00:00");
+
+    assert_eq!(https.http, Some(HttpInfo {
+        status: Some(200),
+        title: Some("Corp VPN - Login".to_string()),
+        server: Some("nginx/1.20.0".to_string()),
+        favicon_hash: Some(-1137974287),
+        redirects: vec!["https://vpn.corp.example/login".to_string()],
+    }));
+
+    let tls = https.tls.as_ref().unwrap();
+    assert_eq!(tls.subject.as_deref(), Some("vpn.corp.example"));
+    assert_eq!(tls.issuer.as_deref(), Some("C=US, O=Let's Encrypt, CN=R3"));
+    assert_eq!(tls.not_after.unwrap().to_rfc3339(), "2024-05-31T00:00:00+00:00");
+    assert_eq!(tls.versions, vec!["TLSv1.2", "TLSv1.3"]);
+    assert_eq!(tls.cipher.as_deref(), Some("TLS_AES_256_GCM_SHA384"));
+    assert_eq!(tls.fingerprint.as_deref(), Some("bb"));
+
+    // Maior nota primeiro, mesmo quando a nota vem como texto
+    let cves: Vec<(&str, Option<f64>, bool)> = https.cves
+        .iter()
+        .map(|cve| (cve.id.as_str(), cve.cvss, cve.verified))
+        .collect();
+    assert_eq!(cves, vec![("CVE-2021-23017", Some(7.7), true), ("CVE-2021-3618", Some(5.8), false)]);
+
+    let ssh = result.services.iter().find(|s| s.port == 22).unwrap();
+    assert_eq!(ssh.cves[0].id, "CVE-2023-48795");
+    assert!(ssh.http.is_none() && ssh.tls.is_none());
+}
+
+#[tokio::test]
+async fn shodan_without_api_key_is_skipped() {
+    let module = ShodanModule::new(ShodanConfig::default(), HttpClient::default());
+    let error = module.scan(&"corp.example".parse().unwrap()).await.unwrap_err();
+
+    assert!(error.downcast_ref::<ModuleSkipped>().is_some());
+}
+
+#[tokio::test]
+async fn wayback_parses_cdx_rows() {
+    let app = Router::new().route(
+        "/cdx/search/cdx",
+        get(|Query(params): Query<HashMap<String, String>>| async move {
+            assert_eq!(params["url"], "*.corp.example");
+            assert_eq!(params["limit"], "25");
+            Json(json!([
+                ["urlkey", "timestamp", "original", "mimetype", "statuscode", "digest", "length"],
+                ["example,corp)/", "20230105101112", "https://corp.example/", "text/html", "200", "AAA", "512"],
+                ["example,corp,www)/login", "20220301000000", "https://www.corp.example/login", "text/html", "302", "BBB", "128"],
+                ["example,corp)/broken", "20220301000000", "not a url", "text/html", "-", "CCC", "0"]
+            ]))
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let module = WaybackModule::new(WaybackConfig { base_url, limit: 25, ..Default::default() }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    let urls: Vec<(&str, Option<u16>)> = result.urls
+        .iter()
+        .map(|u| (u.url.as_str(), u.status_code))
+        .collect();
+    assert_eq!(urls, vec![
+        ("https://corp.example/", Some(200)),
+        ("https://www.corp.example/login", Some(302)),
+    ]);
+
+    let first = &result.urls[0];
+    assert_eq!(first.last_seen.unwrap().to_rfc3339(), "2023-01-05T10:11:12+00:00");
+    assert_eq!(first.source, "Wayback Machine");
+}
+
+#[tokio::test]
+async fn wayback_with_only_header_returns_nothing() {
+    let app = Router::new().route(
+        "/cdx/search/cdx",
+        get(|| async { Json(Value::Array(vec![json!(["urlkey", "timestamp", "original"])])) }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let module = WaybackModule::new(WaybackConfig { base_url, ..Default::default() }, HttpClient::default());
+    let result = module.scan(&"corp.example".parse().unwrap()).await.unwrap();
+
+    assert!(result.urls.is_empty());
+}
+
+#[tokio::test]
+async fn modules_receive_the_typed_target() {
+    let app = Router::new().route(
+        "/shodan/host/search",
+        get(|Query(params): Query<HashMap<String, String>>| async move {
+            // O host da URL é um IP: a consulta usa `ip:`, sem redetectar o tipo
+            assert!(params["query"].starts_with("ip:192.0.2.10"), "{}", params["query"]);
+            Json(json!({ "total": 0, "matches": [] }))
+        }),
+    );
+    let base_url = common::spawn_server(app).await;
+
+    let target: Target = "http://192.0.2.10:8080/admin".parse().unwrap();
+
+    let shodan = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+    assert!(shodan.scan(&target).await.unwrap().ips.is_empty());
+
+    // Módulos de nomes ignoram alvos sem domínio, sem consultar a rede
+    let error = CrtShModule::new(CrtShConfig::default(), HttpClient::default())
+        .scan(&target)
+        .await
+        .unwrap_err();
+    let skipped = error.downcast_ref::<ModuleSkipped>().expect("módulo ignorado");
+    assert!(skipped.0.contains("URL"), "{}", skipped.0);
+}
+
+#[tokio::test]
+async fn shodan_searches_netblocks_and_asns() {
+    let app = Router::new()
+        .route(
+            "/shodan/host/search",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                let query = &params["query"];
+                assert!(query.starts_with("net:192.0.2.0/24") || query.starts_with("asn:AS64500"), "{}", query);
+                Json(json!({
+                    "total": 1,
+                    "matches": [{
+                        "ip_str": "192.0.2.5",
+                        "port": 25,
+                        "transport": "tcp",
+                        "product": "Postfix",
+                        "hostnames": ["mx1.mail.test"]
+                    }]
+                }))
+            }),
+        )
+        .route("/shodan/host/:ip", get(|| async { StatusCode::NOT_FOUND }));
+    let base_url = common::spawn_server(app).await;
+
+    let module = ShodanModule::new(ShodanConfig {
+        api_key: "test-key".to_string(),
+        base_url,
+        ..Default::default()
+    }, HttpClient::default());
+
+    for target in ["192.0.2.77/24", "AS64500"] {
+        let result = module.scan(&target.parse().unwrap()).await.unwrap();
+
+        // Sem domínio, os nomes dos IPs encontrados são mantidos com o IP
+        let subdomains: Vec<(&str, Option<&str>)> = result.subdomains
+            .iter()
+            .map(|s| (s.name.as_str(), s.ip.as_deref()))
+            .collect();
+        assert_eq!(subdomains, vec![("mx1.mail.test", Some("192.0.2.5"))], "{}", target);
+        assert_eq!(result.services[0].port, 25);
+    }
+}
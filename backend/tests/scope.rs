@@ -0,0 +1,113 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use cortex_passivemap::{
+    registry::ModuleRegistry,
+    scope::Scope,
+    target::Target,
+    types::{Certificate, ModuleResult, Subdomain, TargetRequest, Url},
+    OsintModule,
+};
+use std::sync::Arc;
+
+// Módulo que devolve nomes dentro e fora do escopo de corp.example
+struct NoisyModule;
+
+#[async_trait]
+impl OsintModule for NoisyModule {
+    fn name(&self) -> &'static str {
+        "noisy"
+    }
+
+    async fn scan(&self, _target: &Target) -> Result<ModuleResult> {
+        let subdomain = |name: &str| Subdomain {
+            name: name.to_string(),
+            ip: None,
+            cnames: Vec::new(),
+            wildcard: false,
+            first_seen: None,
+            last_seen: None,
+            issuer: None,
+            certificates: Vec::new(),
+            records: Vec::new(),
+            source: self.name().to_string(),
+            sources: Vec::new(),
+        };
+        let url = |url: &str| Url {
+            url: url.to_string(),
+            status_code: None,
+            first_seen: None,
+            last_seen: None,
+            source: self.name().to_string(),
+        };
+        let certificate = |id: &str, names: &[&str]| Certificate {
+            id: id.to_string(),
+            serial_number: None,
+            issuer: None,
+            common_name: None,
+            names: names.iter().map(|name| name.to_string()).collect(),
+            not_before: None,
+            not_after: None,
+            logged_at: None,
+            source: self.name().to_string(),
+        };
+
+        Ok(ModuleResult {
+            subdomains: vec![
+                subdomain("www.corp.example"),
+                subdomain("evilcorp.example"),
+                subdomain("corp.example.attacker.test"),
+                subdomain("api.dev.corp.example"),
+                subdomain("img.corp-cdn.test"),
+            ],
+            urls: vec![
+                url("https://www.corp.example/login"),
+                url("https://corp.example.attacker.test/corp.example"),
+                url("not a url"),
+            ],
+            certificates: vec![
+                certificate("1", &["*.corp.example"]),
+                certificate("2", &["evilcorp.example"]),
+            ],
+            ..Default::default()
+        })
+    }
+}
+
+#[tokio::test]
+async fn out_of_scope_data_never_reaches_the_response() {
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(NoisyModule));
+
+    let scope = Scope::new("corp.example".parse().unwrap())
+        .unwrap()
+        .with_patterns(&["corp-cdn.test".to_string()], &["*.dev.corp.example".to_string()])
+        .unwrap();
+    let response = registry.scan(&scope).await;
+
+    let names: Vec<&str> = response.subdomains.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["img.corp-cdn.test", "www.corp.example"]);
+
+    let urls: Vec<&str> = response.urls.iter().map(|u| u.url.as_str()).collect();
+    assert_eq!(urls, vec!["https://www.corp.example/login"]);
+
+    let certificates: Vec<&str> = response.certificates.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(certificates, vec!["1"]);
+
+    // As contagens do módulo refletem apenas o que ficou no escopo
+    let counts = response.modules[0].counts;
+    assert_eq!((counts.subdomains, counts.urls, counts.certificates), (2, 1, 1));
+}
+
+#[test]
+fn request_patterns_accept_lists_and_comma_separated_strings() {
+    let request: TargetRequest = serde_json::from_str(
+        r#"{ "target": "corp.example", "include": ["corp-cdn.test", " "], "exclude": "*.dev.corp.example, legacy.corp.example" }"#,
+    )
+    .unwrap();
+
+    assert_eq!(request.include, vec!["corp-cdn.test"]);
+    assert_eq!(request.exclude, vec!["*.dev.corp.example", "legacy.corp.example"]);
+
+    let request: TargetRequest = serde_json::from_str(r#"{ "target": "corp.example" }"#).unwrap();
+    assert!(request.include.is_empty() && request.exclude.is_empty());
+}
@@ -0,0 +1,210 @@
+use cortex_passivemap::{
+    config::{DnsConfig, TakeoverConfig},
+    dns::DnsResolver,
+    events::{stream_scan, ScanEvent},
+    http::HttpClient,
+    registry::ModuleRegistry,
+    scope::Scope,
+    target::Target,
+    takeover::TakeoverDetector,
+    types::{AppState, Confidence, Finding, ModuleResult, Subdomain, TakeoverCandidate},
+    OsintModule,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use common::dns::{name, spawn_dns, Zone};
+use hickory_proto::rr::{
+    rdata::{A, CNAME},
+    RData,
+};
+use std::sync::Arc;
+
+mod common;
+
+fn zone() -> Zone {
+    let a = |ip: &str| RData::A(A(ip.parse().unwrap()));
+    let cname = |target: &str| RData::CNAME(CNAME(name(target)));
+    Zone::from([
+        ("www.corp.example.", vec![a("192.0.2.10")]),
+        // Bucket removido: o endpoint do S3 ainda responde, mas com NoSuchBucket
+        ("files.corp.example.", vec![cname("files-corp.s3.amazonaws.com.")]),
+        ("files-corp.s3.amazonaws.com.", vec![a("52.216.0.1")]),
+        // Site ativo no GitHub Pages
+        ("docs.corp.example.", vec![cname("corp.github.io.")]),
+        ("corp.github.io.", vec![a("185.199.108.153")]),
+        // App do Azure excluído: o CNAME aponta para um nome inexistente
+        ("old.corp.example.", vec![cname("corp-old.azurewebsites.net.")]),
+        // Fornecedor desconhecido, também inexistente
+        ("legacy.corp.example.", vec![cname("legacy.vendor.test.")]),
+    ])
+}
+
+// Módulo que devolve os subdomínios da zona, sem IP
+struct StaticModule;
+
+#[async_trait]
+impl OsintModule for StaticModule {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn scan(&self, target: &Target) -> anyhow::Result<ModuleResult> {
+        Ok(ModuleResult {
+            subdomains: ["www", "files", "docs", "old", "legacy"]
+                .iter()
+                .map(|label| Subdomain {
+                    name: format!("{}.{}", label, target),
+                    ip: None,
+                    cnames: Vec::new(),
+                    wildcard: false,
+                    first_seen: None,
+                    last_seen: None,
+                    issuer: None,
+                    certificates: Vec::new(),
+                    records: Vec::new(),
+                    source: "crt.sh".to_string(),
+                    sources: Vec::new(),
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+async fn registry(takeover: TakeoverConfig) -> ModuleRegistry {
+    let (addr, _) = spawn_dns(zone()).await;
+    let dns = DnsConfig {
+        enabled: true,
+        nameservers: vec![addr.to_string()],
+        timeout_secs: 2,
+        attempts: 1,
+        ..Default::default()
+    };
+
+    // As páginas dos candidatos vêm de fixtures, sem acesso à rede
+    let http = HttpClient::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/takeover"));
+
+    let mut registry = ModuleRegistry::new()
+        .with_resolver(DnsResolver::from_config(&dns).unwrap())
+        .with_takeover(TakeoverDetector::from_config(&takeover, http).unwrap());
+    registry.register(Arc::new(StaticModule));
+    registry
+}
+
+fn candidates(findings: &[Finding]) -> Vec<&TakeoverCandidate> {
+    findings
+        .iter()
+        .map(|finding| match finding {
+            Finding::TakeoverCandidate(candidate) => candidate,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn flags_unclaimed_fingerprints_and_dangling_cnames() {
+    let response = registry(TakeoverConfig::default()).await.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+    let found = candidates(&response.findings);
+
+    let summary: Vec<(&str, &str, &str, Confidence)> = found
+        .iter()
+        .map(|c| (c.subdomain.as_str(), c.cname.as_str(), c.provider.as_str(), c.confidence))
+        .collect();
+    assert_eq!(summary, vec![
+        ("files.corp.example", "files-corp.s3.amazonaws.com", "AWS S3", Confidence::High),
+        ("legacy.corp.example", "legacy.vendor.test", "Desconhecido", Confidence::Low),
+        ("old.corp.example", "corp-old.azurewebsites.net", "Microsoft Azure", Confidence::High),
+    ]);
+
+    let files = found.iter().find(|c| c.subdomain == "files.corp.example").unwrap();
+    assert!(files.evidence.contains("NoSuchBucket"), "{}", files.evidence);
+
+    let old = found.iter().find(|c| c.subdomain == "old.corp.example").unwrap();
+    assert!(old.evidence.contains("NXDOMAIN"), "{}", old.evidence);
+
+    // O CNAME pendente também fica registrado no subdomínio
+    let subdomain = response.subdomains.iter().find(|s| s.name == "old.corp.example").unwrap();
+    assert_eq!(subdomain.cnames, vec!["corp-old.azurewebsites.net"]);
+    assert!(subdomain.ip.is_none());
+}
+
+#[tokio::test]
+async fn streamed_scans_emit_enrichment_items() {
+    let state = Arc::new(AppState::new(registry(TakeoverConfig::default()).await));
+    let events: Vec<ScanEvent> = stream_scan(state, Scope::new("corp.example".parse().unwrap()).unwrap())
+        .collect()
+        .await;
+
+    let kinds: Vec<&str> = events.iter().map(ScanEvent::kind).collect();
+    assert_eq!(kinds.first(), Some(&"module_started"));
+    assert_eq!(kinds.last(), Some(&"scan_complete"));
+
+    // Resolução DNS: o subdomínio volta com o IP, e o IP chega como item próprio
+    let www = events.iter().find_map(|event| match event {
+        ScanEvent::Subdomain { module, subdomain } if module == "DNS" && subdomain.name == "www.corp.example" => {
+            Some(subdomain)
+        }
+        _ => None,
+    });
+    assert_eq!(www.and_then(|s| s.ip.as_deref()), Some("192.0.2.10"));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ScanEvent::Ip { module, ip } if module == "DNS" && ip.ip == "192.0.2.10"
+    )));
+
+    // Os candidatos a takeover também chegam antes do resultado final
+    let findings = events
+        .iter()
+        .filter(|event| matches!(event, ScanEvent::Finding { module, .. } if module == "takeover"))
+        .count();
+    assert_eq!(findings, 3);
+}
+
+#[tokio::test]
+async fn http_probe_can_be_disabled() {
+    let config = TakeoverConfig { http_probe: false, ..Default::default() };
+    let response = registry(config).await.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+
+    let subdomains: Vec<&str> = candidates(&response.findings)
+        .iter()
+        .map(|c| c.subdomain.as_str())
+        .collect();
+    assert_eq!(subdomains, vec!["legacy.corp.example", "old.corp.example"]);
+}
+
+#[tokio::test]
+async fn providers_are_loaded_from_a_data_file() {
+    let path = std::env::temp_dir().join(format!("cortex-takeover-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(&path, r#"
+        [[provider]]
+        name = "Vendor"
+        cname = ['\.vendor\.test$']
+        nxdomain = true
+    "#).unwrap();
+
+    let config = TakeoverConfig {
+        fingerprints_path: Some(path.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let response = registry(config).await.scan(&Scope::new("corp.example".parse().unwrap()).unwrap()).await;
+    std::fs::remove_file(&path).unwrap();
+
+    let summary: Vec<(&str, &str, Confidence)> = candidates(&response.findings)
+        .iter()
+        .map(|c| (c.subdomain.as_str(), c.provider.as_str(), c.confidence))
+        .collect();
+    // O arquivo substitui a lista embutida: o Azure deixa de ser reconhecido
+    assert_eq!(summary, vec![
+        ("legacy.corp.example", "Vendor", Confidence::High),
+        ("old.corp.example", "Desconhecido", Confidence::Low),
+    ]);
+}
+
+#[test]
+fn builtin_fingerprints_parse() {
+    let detector = TakeoverDetector::from_config(&TakeoverConfig::default(), HttpClient::default()).unwrap();
+    let names: Vec<&str> = detector.providers().iter().map(|p| p.name.as_str()).collect();
+
+    for provider in ["AWS S3", "GitHub Pages", "Heroku", "Microsoft Azure", "Fastly"] {
+        assert!(names.contains(&provider), "{} ausente", provider);
+    }
+}
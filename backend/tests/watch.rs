@@ -0,0 +1,156 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{extract::State, routing::post, Json, Router};
+use cortex_passivemap::{
+    registry::ModuleRegistry,
+    target::Target,
+    types::{AppState, ModuleResult, Service, Subdomain},
+    watch::{run_watch, WatchAlert, WatchRequest, Watchlist},
+    OsintModule,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::sync::mpsc;
+
+mod common;
+
+// Módulo falso que descobre um subdomínio e uma porta nova a cada execução
+#[derive(Default)]
+struct GrowingModule {
+    runs: AtomicUsize,
+}
+
+#[async_trait]
+impl OsintModule for GrowingModule {
+    fn name(&self) -> &'static str {
+        "Growing"
+    }
+
+    async fn scan(&self, target: &Target) -> Result<ModuleResult> {
+        let runs = self.runs.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut result = ModuleResult::default();
+
+        for i in 0..runs {
+            result.subdomains.push(Subdomain {
+                name: format!("host{}.{}", i, target),
+                // Serviços só passam pelo escopo com o IP de um nome do alvo
+                ip: Some("192.0.2.10".to_string()),
+                cnames: Vec::new(),
+                wildcard: false,
+                first_seen: None,
+                last_seen: None,
+                issuer: None,
+                certificates: Vec::new(),
+                records: Vec::new(),
+                source: self.name().to_string(),
+                sources: Vec::new(),
+            });
+            result.services.push(Service {
+                ip: "192.0.2.10".to_string(),
+                port: 8000 + i as u16,
+                service: "http".to_string(),
+                banner: None,
+                source: self.name().to_string(),
+                ..Default::default()
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+// Servidor HTTP local que recebe os alertas do webhook
+async fn webhook_stand_in() -> (String, mpsc::UnboundedReceiver<WatchAlert>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    async fn receive(
+        State(tx): State<mpsc::UnboundedSender<WatchAlert>>,
+        Json(alert): Json<WatchAlert>,
+    ) {
+        tx.send(alert).unwrap();
+    }
+
+    let app = Router::new().route("/hook", post(receive)).with_state(tx);
+    let base_url = common::spawn_server(app).await;
+
+    (format!("{}/hook", base_url), rx)
+}
+
+#[tokio::test]
+async fn alerts_webhook_when_surface_grows() {
+    let (webhook, mut alerts) = webhook_stand_in().await;
+
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(GrowingModule::default()));
+
+    let state = Arc::new(AppState::new(registry).with_watchlist(Watchlist::new(Some(webhook))));
+    let watch = state.watchlist
+        .add("corp.example", &WatchRequest {
+            target: "corp.example".to_string(),
+            interval: "@hourly".to_string(),
+            webhook: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        })
+        .unwrap();
+
+    // Primeira execução apenas estabelece a linha de base
+    assert!(run_watch(&state, &watch.id).await.is_none());
+
+    let alert = run_watch(&state, &watch.id).await.expect("alerta esperado");
+    assert_eq!(alert.new_subdomains, vec!["host1.corp.example".to_string()]);
+    assert_eq!(alert.new_services.len(), 1);
+    assert_eq!(alert.new_services[0].port, 8001);
+
+    let delivered = alerts.recv().await.unwrap();
+    assert_eq!(delivered.watch_id, watch.id);
+    assert_eq!(delivered.target, "corp.example");
+    assert_eq!(delivered.new_subdomains, alert.new_subdomains);
+
+    let watches = state.watchlist.list();
+    assert_eq!(watches[0].last_scan_id.as_deref(), Some(alert.to.as_str()));
+    assert!(watches[0].next_run > watches[0].created_at);
+}
+
+#[test]
+fn parses_cron_like_intervals() {
+    use cortex_passivemap::watch::parse_interval;
+
+    assert_eq!(parse_interval("@daily"), Ok(86400));
+    assert_eq!(parse_interval("every 6h"), Ok(6 * 3600));
+    assert_eq!(parse_interval("30m"), Ok(1800));
+    assert!(parse_interval("10s").is_err());
+    assert!(parse_interval("5 parsecs").is_err());
+
+    // Intervalos acima de um ano seriam agendados além do calendário
+    assert_eq!(parse_interval("every 365d"), Ok(365 * 86400));
+    assert!(parse_interval("every 366d").is_err());
+    assert!(parse_interval("every 100000000d").is_err());
+    assert!(parse_interval("99999999999999999999w").is_err());
+}
+
+#[tokio::test]
+async fn oversized_intervals_never_reach_the_scheduler() {
+    let mut registry = ModuleRegistry::new();
+    registry.register(Arc::new(GrowingModule::default()));
+    let state = Arc::new(AppState::new(registry).with_watchlist(Watchlist::new(None)));
+
+    let request = |interval: &str| WatchRequest {
+        target: "corp.example".to_string(),
+        interval: interval.to_string(),
+        webhook: None,
+        include: Vec::new(),
+        exclude: Vec::new(),
+    };
+    assert!(state.watchlist.add("corp.example", &request("every 100000000d")).is_err());
+
+    // O maior intervalo aceito agenda a próxima execução sem estourar a data
+    let watch = state.watchlist.add("corp.example", &request("every 365d")).unwrap();
+    run_watch(&state, &watch.id).await;
+    run_watch(&state, &watch.id).await;
+
+    let watches = state.watchlist.list();
+    assert!(watches[0].next_run > watches[0].created_at + chrono::Duration::days(364));
+}
@@ -382,8 +382,8 @@ export default function ExportOptions({ onExport, data, graphRef }: ExportOption
         processDataArray(
           'Endereços IP', 
           data.ips, 
-          (ip) => `${ip.ip} | ${ip.ports?.join(', ') || 'N/A'} | ${ip.isp || 'N/A'} | ${ip.location || 'N/A'}`,
-          ['Endereço IP', 'Portas', 'ISP', 'Localização']
+          (ip) => `${ip.ip} | ${ip.asn ? `AS${ip.asn}` : 'N/A'} | ${ip.as_org || 'N/A'} | ${ip.country || 'N/A'} | ${ip.provider || 'N/A'}`,
+          ['Endereço IP', 'ASN', 'Organização', 'País', 'Provedor']
         );
       }
       
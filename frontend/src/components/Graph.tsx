@@ -8,7 +8,7 @@ interface GraphProps {
   data: {
     target: string;
     subdomains: any[];
-    ips: string[];
+    ips: any[];
     services: any[];
   };
   onNodeClick?: (node: any) => void;
@@ -148,7 +148,8 @@ const Graph: React.FC<GraphProps> = ({ data, onNodeClick }) => {
       // Limita o número de IPs visíveis
       const visibleIPs = data.ips.slice(0, maxNodesVisible);
       
-      visibleIPs.forEach(ip => {
+      visibleIPs.forEach(info => {
+        const ip: string = typeof info === 'string' ? info : info.ip;
         if (!nodes.some(node => node.id === ip)) {
           nodes.push({
             id: ip,
@@ -87,6 +87,12 @@ const processDataForGraph = (data: any): GraphData => {
         if (data.target) {
           edges.push({ source: `domain-${data.target}`, target: subdomainId, type: 'has_subdomain' });
         }
+        // IP obtido pelo Shodan ou pela resolução DNS
+        if (subdomain.ip) {
+          const ipId = `ip-${subdomain.ip}`;
+          addNode(ipId, subdomain.ip, 'ip');
+          edges.push({ source: subdomainId, target: ipId, type: 'resolves_to' });
+        }
       }
     });
   }
@@ -47,7 +47,8 @@ export default function ResultsTable({ data, type }: ResultsTableProps) {
         
     // Processamento adicional para normalizar os dados
     return rawItems.map(item => {
-      const processedItem = { ...item };
+      // Escaneamentos antigos trazem os IPs como strings
+      const processedItem = typeof item === 'string' ? { ip: item } : { ...item };
       
       // Processar strings que contêm múltiplos subdomínios
       if (type === 'subdomains' && typeof item.name === 'string' && item.name.includes(' ')) {
@@ -161,9 +162,10 @@ export default function ResultsTable({ data, type }: ResultsTableProps) {
       case 'ips':
         return [
           { key: 'ip', label: 'IP', sortable: true },
-          { key: 'ports', label: 'Portas', sortable: false },
-          { key: 'isp', label: 'ISP', sortable: true },
-          { key: 'location', label: 'Localização', sortable: true },
+          { key: 'asn', label: 'ASN', sortable: true },
+          { key: 'as_org', label: 'Organização', sortable: true },
+          { key: 'country', label: 'País', sortable: true },
+          { key: 'provider', label: 'Provedor', sortable: true },
         ];
       case 'services':
         return [
@@ -297,6 +299,13 @@ export default function ResultsTable({ data, type }: ResultsTableProps) {
     if (value === undefined || value === null) {
       return <span className="text-gray-400">N/A</span>;
     }
+
+    // Bordas de CDN não fazem parte da infraestrutura do alvo
+    if (key === 'provider') {
+      return item.kind === 'cdn'
+        ? <span className="text-yellow-400">{value} (CDN)</span>
+        : <span>{value} (hospedagem)</span>;
+    }
     
     if (Array.isArray(value)) {
       // Exibir arrays como lista para melhor visualização
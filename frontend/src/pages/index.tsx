@@ -124,9 +124,11 @@ export default function Home() {
       doc.text(`IPs (${results.ips.length})`, margin, yPos);
       yPos += 10;
       
-      results.ips.slice(0, 15).forEach((ip: string, index: number) => {
+      results.ips.slice(0, 15).forEach((info: any, index: number) => {
+        const ip = typeof info === 'string' ? info : info.ip;
+        const network = info.asn ? ` - AS${info.asn} ${info.provider || info.as_org || ''}` : '';
         doc.setFontSize(10);
-        doc.text(`${index + 1}. ${ip}`, margin + 5, yPos);
+        doc.text(`${index + 1}. ${ip}${network}`, margin + 5, yPos);
         yPos += 6;
       });
       